@@ -0,0 +1,397 @@
+//! Play seeded rounds against the built-in solver and configured engines, to
+//! compare them and notice when one gets slower or worse.
+
+use std::io::Write;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use serde_derive::Serialize;
+
+use crate::config::{AppConfig, EngineConfig};
+use crate::board;
+use crate::positionning::LogicalPos;
+use crate::game::{GameState, Reachability, Target};
+use crate::solver::{Outcome, Solver, SolverLimits};
+use crate::engine::Engine;
+
+use super::error::*;
+
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReportFormat {
+    Csv,
+    Json,
+}
+
+
+#[derive(Debug, Clone)]
+pub struct BenchOptions {
+    pub rounds: usize,
+    /// Seed of the first round, the next ones following it.
+    pub first_seed: u64,
+    /// Most moves a robot needs on its own to reach the target of a round,
+    /// rounds being harder with more. Past 5, the built-in solver may reach
+    /// its limits before finding the shortest solution.
+    pub max_target_moves: usize,
+    pub format: ReportFormat,
+}
+
+
+impl Default for BenchOptions {
+    fn default() -> BenchOptions {
+        BenchOptions {
+            rounds: 20,
+            first_seed: 0,
+            max_target_moves: 5,
+            format: ReportFormat::Csv,
+        }
+    }
+}
+
+
+/// How the search of one solver ended.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BenchStatus {
+    Solved,
+    /// The built-in solver explored every position without solving the
+    /// round.
+    Unsolvable,
+    /// The built-in solver reached its limits first.
+    GaveUp,
+    /// Another engine answered `nosolution`.
+    NoSolution,
+    /// The engine failed, or did not answer in time.
+    Failed,
+}
+
+
+impl BenchStatus {
+    fn name(self) -> &'static str {
+        match self {
+            BenchStatus::Solved => "solved",
+            BenchStatus::Unsolvable => "unsolvable",
+            BenchStatus::GaveUp => "gave-up",
+            BenchStatus::NoSolution => "no-solution",
+            BenchStatus::Failed => "failed",
+        }
+    }
+}
+
+
+/// How one solver did on one round.
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchRecord {
+    pub seed: u64,
+    /// Name of the engine in the config, engines of the same program being
+    /// told apart by their arguments.
+    pub solver: String,
+    pub status: BenchStatus,
+    /// Time from `go` to the answer, every solver being a running engine.
+    pub time_ms: f64,
+    /// Positions explored, when the solver tells.
+    pub nodes: Option<usize>,
+    /// Length of the solution found, if any.
+    pub moves: Option<usize>,
+    /// Length of the shortest solution, as found by the built-in solver.
+    pub optimal_moves: Option<usize>,
+    /// Whether the solution actually brings the robot on its target.
+    pub valid: bool,
+    /// Whether the solution is as short as it gets, unknown when the
+    /// built-in solver gave up, and left out for the built-in solver itself.
+    pub optimal: Option<bool>,
+    pub error: Option<String>,
+}
+
+
+/// Play `options.rounds` rounds, each against the built-in solver then every
+/// engine of `config`, boards, robots and targets following the seed of the
+/// round.
+pub fn run(config: &Rc<AppConfig>, options: &BenchOptions) -> Result<Vec<BenchRecord>> {
+    let builder = board::Builder::new(config);
+    // The built-in solver answers through the engine protocol too, for its
+    // time to compare with those of engines. It comes first.
+    let builtin_config = EngineConfig::builtin(std::env::current_exe()?);
+    let mut engines = std::iter::once(&builtin_config)
+        .chain(config.engines.iter())
+        .map(|engine_config| Engine::spawn(engine_config)
+             .map(|engine| (engine, engine_config)))
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    let mut records = Vec::new();
+
+    for seed in (0..options.rounds as u64).map(|round| options.first_seed + round) {
+        let mut state = GameState::new();
+        builder.build_with_seed(&mut state, seed)?;
+        let mut rng = StdRng::seed_from_u64(seed);
+        state.reset_rand_pos_with(&mut rng);
+        state.target = pick_target(&state, &mut rng, options.max_target_moves);
+        let target = match state.target.clone() {
+            Some(target) => target,
+            None => continue,
+        };
+
+        // Shortest solution, in the same process, solutions of engines being
+        // compared to it.
+        let solver = Solver::new(state.board.as_ref(), SolverLimits::default());
+        let reference = solver.search_for(&state, &target);
+        let optimal_moves = match reference {
+            Outcome::Solved(ref solution) => Some(solution.steps.len()),
+            _ => None,
+        };
+
+        for (index, (engine, engine_config)) in engines.iter_mut().enumerate() {
+            let is_builtin = index == 0;
+            let started = Instant::now();
+            let record = match engine.search(&state, engine_config.max_moves) {
+                Ok(result) => {
+                    let moves = result.steps.as_ref().map(|steps| steps.len());
+                    let valid = result.steps.as_ref()
+                        .is_some_and(|steps| solver.is_solution(&state, &target, steps));
+                    let status = match (moves, &reference) {
+                        (Some(_), _) => BenchStatus::Solved,
+                        (None, _) if !is_builtin => BenchStatus::NoSolution,
+                        (None, Outcome::Unsolvable { .. }) => BenchStatus::Unsolvable,
+                        (None, _) => BenchStatus::GaveUp,
+                    };
+                    BenchRecord {
+                        seed,
+                        solver: engine_config.name.clone(),
+                        status,
+                        time_ms: millis(result.elapsed),
+                        nodes: result.nodes,
+                        moves,
+                        optimal_moves,
+                        valid,
+                        optimal: optimal_moves
+                            .filter(|_| !is_builtin)
+                            .map(|optimal| valid && moves == Some(optimal)),
+                        error: None,
+                    }
+                },
+                Err(error) => BenchRecord {
+                    seed,
+                    solver: engine_config.name.clone(),
+                    status: BenchStatus::Failed,
+                    time_ms: millis(started.elapsed()),
+                    nodes: None,
+                    moves: None,
+                    optimal_moves,
+                    valid: false,
+                    optimal: optimal_moves.filter(|_| !is_builtin).map(|_| false),
+                    error: Some(error.to_string()),
+                },
+            };
+            records.push(record);
+        }
+    }
+
+    Ok(records)
+}
+
+
+// Target of a round the built-in solver can solve: a target of the board
+// when one is close enough to its robot, any cell a robot can reach on its
+// own otherwise.
+fn pick_target<R>(state: &GameState, rng: &mut R, max_moves: usize) -> Option<Target>
+    where R: Rng
+{
+    let within_reach = |target: &Target| {
+        Reachability::compute(state, target.robot)
+            .and_then(|reachability| reachability.moves_to(&target.pos))
+            .is_some_and(|moves| (1..=max_moves).contains(&moves))
+    };
+
+    let mut targets = state.targets.clone();
+    targets.shuffle(rng);
+    if let Some(target) = targets.into_iter().find(|target| within_reach(target)) {
+        return Some(target);
+    }
+
+    let robot = state.robots.choose(rng)?.id;
+    let reachability = Reachability::compute(state, robot)?;
+    let dimensions = reachability.dimensions();
+    let cells: Vec<LogicalPos> = (0..dimensions.height)
+        .flat_map(|y| (0..dimensions.width).map(move |x| LogicalPos { x, y }))
+        .filter(|pos| reachability.moves_to(pos)
+                .is_some_and(|moves| (1..=max_moves).contains(&moves)))
+        .collect();
+    cells.choose(rng).map(|pos| Target::new(robot, *pos))
+}
+
+
+/// Write `records` as a CSV table or a JSON array.
+pub fn write_report<W>(records: &[BenchRecord], format: ReportFormat, out: &mut W) -> Result<()>
+    where W: Write
+{
+    match format {
+        ReportFormat::Json => {
+            serde_json::to_writer_pretty(&mut *out, records)?;
+            writeln!(out)?;
+        },
+        ReportFormat::Csv => {
+            writeln!(out, "seed,solver,status,time_ms,nodes,moves,optimal_moves,valid,optimal,error")?;
+            for record in records.iter() {
+                writeln!(out, "{},{},{},{:.3},{},{},{},{},{},{}",
+                         record.seed,
+                         csv_field(&record.solver),
+                         record.status.name(),
+                         record.time_ms,
+                         optional(record.nodes),
+                         optional(record.moves),
+                         optional(record.optimal_moves),
+                         record.valid,
+                         optional(record.optimal),
+                         csv_field(record.error.as_deref().unwrap_or("")))?;
+            }
+        },
+    }
+
+    Ok(())
+}
+
+
+/// One line per solver: rounds solved, optimal solutions and mean time.
+pub fn summary(records: &[BenchRecord]) -> Vec<String> {
+    let mut solvers: Vec<&str> = Vec::new();
+    for record in records.iter() {
+        if !solvers.contains(&record.solver.as_str()) {
+            solvers.push(&record.solver);
+        }
+    }
+
+    solvers.into_iter()
+        .map(|solver| {
+            let rounds: Vec<&BenchRecord> = records.iter()
+                .filter(|record| record.solver == solver)
+                .collect();
+            let solved = rounds.iter().filter(|record| record.valid).count();
+            let time_ms = rounds.iter().map(|record| record.time_ms).sum::<f64>() / rounds.len() as f64;
+            let mut line = format!("{}: {}/{} solved", solver, solved, rounds.len());
+
+            // The built-in solver is the reference, never compared to itself.
+            if rounds.iter().any(|record| record.optimal.is_some()) {
+                let optimal = rounds.iter().filter(|record| record.optimal == Some(true)).count();
+                line.push_str(&format!(", {} optimal", optimal));
+            }
+            for status in [BenchStatus::Unsolvable, BenchStatus::GaveUp,
+                           BenchStatus::NoSolution, BenchStatus::Failed].iter() {
+                let count = rounds.iter().filter(|record| record.status == *status).count();
+                if count > 0 {
+                    line.push_str(&format!(", {} {}", count, status.name()));
+                }
+            }
+
+            line.push_str(&format!(", {:.1} ms on average", time_ms));
+            line
+        })
+        .collect()
+}
+
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs() as f64 * 1000.0 + f64::from(duration.subsec_nanos()) / 1_000_000.0
+}
+
+
+fn optional<T>(value: Option<T>) -> String where T: ToString {
+    value.map_or_else(String::new, |value| value.to_string())
+}
+
+
+// Quote fields that would break the table.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    }
+    else {
+        value.to_string()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{BenchRecord, BenchStatus, ReportFormat, csv_field, summary, write_report};
+
+
+    fn record(solver: &str, status: BenchStatus, moves: Option<usize>) -> BenchRecord {
+        BenchRecord {
+            seed: 7,
+            solver: solver.to_string(),
+            status,
+            time_ms: 2.0,
+            nodes: Some(120),
+            moves,
+            optimal_moves: Some(3),
+            valid: moves.is_some(),
+            optimal: if solver == "rrr-solver" { None } else { Some(moves == Some(3)) },
+            error: None,
+        }
+    }
+
+
+    #[test]
+    fn csv_fields_are_quoted_when_needed() {
+        assert_eq!(csv_field("plain name"), "plain name");
+        assert_eq!(csv_field("a, b"), "\"a, b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+    }
+
+
+    #[test]
+    fn csv_reports_have_a_line_per_record() {
+        let mut failed = record("slow, engine", BenchStatus::Failed, None);
+        failed.nodes = None;
+        failed.error = Some("engine 'slow' did not answer in time".to_string());
+        let records = vec![record("rrr-solver", BenchStatus::Solved, Some(3)), failed];
+
+        let mut out = Vec::new();
+        write_report(&records, ReportFormat::Csv, &mut out).expect("report is written");
+        let report = String::from_utf8(out).expect("report is text");
+
+        assert_eq!(report.lines().collect::<Vec<_>>(), vec![
+            "seed,solver,status,time_ms,nodes,moves,optimal_moves,valid,optimal,error",
+            "7,rrr-solver,solved,2.000,120,3,3,true,,",
+            "7,\"slow, engine\",failed,2.000,,,3,false,false,engine 'slow' did not answer in time",
+        ]);
+    }
+
+
+    #[test]
+    fn json_reports_are_an_array_of_records() {
+        let records = vec![record("fast", BenchStatus::NoSolution, None)];
+
+        let mut out = Vec::new();
+        write_report(&records, ReportFormat::Json, &mut out).expect("report is written");
+        let report: serde_json::Value = serde_json::from_slice(&out).expect("report is JSON");
+
+        assert_eq!(report[0]["solver"], "fast");
+        assert_eq!(report[0]["status"], "no-solution");
+        assert_eq!(report[0]["moves"], serde_json::Value::Null);
+        assert_eq!(report[0]["optimal"], false);
+        assert_eq!(report.as_array().map(Vec::len), Some(1));
+    }
+
+
+    #[test]
+    fn summary_has_a_line_per_solver_in_order() {
+        let mut slow = record("fast", BenchStatus::Solved, Some(4));
+        slow.time_ms = 4.0;
+        let records = vec![
+            record("rrr-solver", BenchStatus::Solved, Some(3)),
+            record("fast", BenchStatus::Solved, Some(3)),
+            record("rrr-solver", BenchStatus::GaveUp, None),
+            slow,
+            record("fast", BenchStatus::Failed, None),
+        ];
+
+        assert_eq!(summary(&records), vec![
+            "rrr-solver: 1/2 solved, 1 gave-up, 2.0 ms on average",
+            "fast: 2/3 solved, 1 optimal, 1 failed, 2.7 ms on average",
+        ]);
+    }
+}
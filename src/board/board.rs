@@ -1,4 +1,4 @@
-use crate::positionning::{LogicalPos, PosExtra, Way, Hit, SideLength};
+use crate::positionning::{LogicalPos, PosExtra, Way, Hit, Dimensions};
 use crate::moves::MovePossibility;
 use crate::wall::{Wall, Side};
 
@@ -6,12 +6,11 @@ use super::error::*;
 
 
 pub trait Board {
-    fn side_length(&self) -> SideLength;
+    fn dimensions(&self) -> Dimensions;
 
     // Check if position exists on the board.
     fn pos_exists(&self, pos: &LogicalPos) -> bool {
-        let side = self.side_length().0;
-        pos.x < side && pos.y < side
+        self.dimensions().contains(pos)
     }
 
     // Test whether the given position can be used to start a robot on.
@@ -26,18 +25,18 @@ pub trait Board {
     // Indicates the position of hit with the board content.
     fn hit_from(&self, start: &LogicalPos, way: Way) -> Result<Hit>;
 
-    // Find the hit according only to board SideLength.
+    // Find the hit according only to board dimensions.
     fn side_hit(&self, start: &LogicalPos, way: Way) -> Result<Hit> {
         self.if_exists(start)
             .map(|_| {
-                let side = self.side_length().0;
-                assert!(side >= 1);
+                let dimensions = self.dimensions();
+                assert!(dimensions.width >= 1 && dimensions.height >= 1);
 
                 let pos = match way {
                     Way::Up => LogicalPos{ x: start.x, y: 0 },
-                    Way::Down => LogicalPos{ x: start.x, y: side - 1 },
+                    Way::Down => LogicalPos{ x: start.x, y: dimensions.height - 1 },
                     Way::Left => LogicalPos{ x: 0, y: start.y },
-                    Way::Right => LogicalPos{ x: side - 1, y: start.y },
+                    Way::Right => LogicalPos{ x: dimensions.width - 1, y: start.y },
                 };
 
                 let distance = start.distance_to(&pos, way);
@@ -46,8 +45,7 @@ pub trait Board {
     }
 
     fn oob_error(&self, pos: LogicalPos) -> ErrorKind {
-        let side_length = self.side_length();
-        ErrorKind::OutOfBoardPosition(pos, side_length)
+        ErrorKind::OutOfBoardPosition(pos, self.dimensions())
     }
 
     fn if_exists(&self, pos: &LogicalPos) -> Result<()> {
@@ -58,19 +56,19 @@ pub trait Board {
 
 
 pub trait EditableBoard: Board {
-    fn reset(&mut self, side_length: &SideLength) -> Result<()>;
+    fn reset(&mut self, dimensions: &Dimensions) -> Result<()>;
 
     fn put_wall(&mut self, wall: &Wall) -> Result<()>;
     
     fn forbid_cell(&mut self, pos: &LogicalPos) -> Result<()> {
-        let side = self.side_length().0;
+        let dimensions = self.dimensions();
         
         if pos.y > 0 {
             let top_pos = LogicalPos{ y: pos.y - 1, ..*pos };
             self.put_wall(&Wall { pos: top_pos, side: Side::Down })?;
         }
         
-        if pos.y + 1 < side {
+        if pos.y + 1 < dimensions.height {
             let bottom_pos = LogicalPos{ y: pos.y + 1, ..*pos };
             self.put_wall(&Wall { pos: bottom_pos, side: Side::Up })?;
         }
@@ -80,7 +78,7 @@ pub trait EditableBoard: Board {
             self.put_wall(&Wall { pos: left_pos, side: Side::Right })?;
         }
         
-        if pos.x + 1 < side {
+        if pos.x + 1 < dimensions.width {
             let right_pos = LogicalPos{ x: pos.x + 1, ..*pos };
             self.put_wall(&Wall { pos: right_pos, side: Side::Left })?;
         }
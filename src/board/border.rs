@@ -1,7 +1,9 @@
 use std::fmt;
 
+use cgmath::Vector2;
 
-#[derive(Debug)]
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Border {
     TopLeft, // NOTE: default border
     TopRight,
@@ -22,6 +24,28 @@ impl Border {
     pub fn all() -> &'static [Border] {
         &ALL_BORDERS
     }
+
+    pub fn index(&self) -> usize {
+        match self {
+            Border::TopLeft => 0,
+            Border::TopRight => 1,
+            Border::BottomLeft => 2,
+            Border::BottomRight => 3,
+        }
+    }
+
+    // Corner of the board covered by a tile put on this border, as a unit
+    // direction from the center. Tiles are rotated to be placed, so
+    // `BottomLeft` ends up on the bottom-right corner (half-turn) and
+    // `BottomRight` on the bottom-left one (turned left).
+    pub fn outward(&self) -> Vector2<f32> {
+        match self {
+            Border::TopLeft => Vector2::new(-1f32, -1f32),
+            Border::TopRight => Vector2::new(1f32, -1f32),
+            Border::BottomLeft => Vector2::new(1f32, 1f32),
+            Border::BottomRight => Vector2::new(-1f32, 1f32),
+        }
+    }
 }
 
 
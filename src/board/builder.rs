@@ -1,33 +1,84 @@
 use std::rc::Rc;
 
+use rand::SeedableRng;
+use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
+use serde_derive::Deserialize;
 
 use crate::config::AppConfig;
 use crate::game::GameState;
 
+use super::error::*;
+
+
+/// Where walls of a new board come from.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BoardSource {
+    /// Tiles of a random tile set.
+    #[default]
+    Tiles,
+    /// Walls laid out by the generator.
+    Generated,
+}
+
 
 pub struct Builder {
     config: Rc<AppConfig>,
+    source: BoardSource,
 }
 
 
 impl Builder {
     pub fn new(config: &Rc<AppConfig>) -> Builder {
         let config = config.clone();
-        Builder{ config }
+        let source = config.board_source;
+        Builder{ config, source }
+    }
+
+
+    pub fn source(&self) -> BoardSource {
+        self.source
     }
 
 
-    pub fn build_on(&self, world: &mut GameState) {
-        world.board
-            .reset(&self.config.board_side_length)
-            .expect("valid dimension");
+    /// Use `source` for next boards, whatever the config says.
+    pub fn set_source(&mut self, source: BoardSource) {
+        self.source = source;
+    }
+
+
+    /// Build a random board, returning the seed to build it again.
+    pub fn build_on(&self, world: &mut GameState) -> Result<u64> {
+        let seed = rand::random();
+        self.build_with_seed(world, seed)?;
+        Ok(seed)
+    }
+
+
+    /// Build the same board for the same seed, as long as the source and its
+    /// settings are the same. The board of `world` is left untouched when
+    /// the build fails.
+    pub fn build_with_seed(&self, world: &mut GameState, seed: u64) -> Result<()> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut built = GameState::new();
+
+        built.board.reset(&self.config.board_dimensions())?;
+        built.targets = match self.source {
+            BoardSource::Tiles => {
+                let tile_set = self.config.tile_sets.choose(&mut rng)
+                    .ok_or(ErrorKind::NoTileSet)?;
+                built.tiling = Some(tile_set.tiling);
+                tile_set.build_rand(&mut built.board, &mut rng)?
+            },
+            BoardSource::Generated => {
+                self.config.generator.build_rand(&mut built.board, &mut rng)?
+            },
+        };
 
-        // TODO error handling
-        let mut rng = rand::thread_rng();
-        let tile_set = self.config.tile_sets.choose(&mut rng)
-            .expect("config has at least one tile sets");
-        tile_set.build_rand(&mut world.board)
-            .expect("board can be build with tile sets");
+        world.board = built.board;
+        world.targets = built.targets;
+        world.tiling = built.tiling;
+        Ok(())
     }
 }
@@ -1,9 +1,10 @@
 use error_chain::error_chain;
 pub use error_chain::bail; // Re-export
 
-use crate::positionning::{LogicalPos, SideLength};
+use crate::positionning::{LogicalPos, SideLength, Dimensions};
 
 use super::tile_parser_error;
+use super::tiling::Tiling;
 
 
 error_chain! {
@@ -26,14 +27,55 @@ error_chain! {
             display("invalid tile #{} of tile set '{}'", tile_index, tile_set_name),
         }
 
-        OutOfBoardPosition(pos: LogicalPos, side_length: SideLength) {
+        OutOfBoardPosition(pos: LogicalPos, dimensions: Dimensions) {
             description("out-of-board position"),
-            display("out-of-board position {0:?} (board={1}x{1})", pos, side_length),
+            display("out-of-board position {:?} (board={})", pos, dimensions),
         }
 
-        EmptyTileSet {
+        EmptyTileSet(tile_set_name: String) {
             description("empty tile set"),
-            display("empty tile set"),
+            display("tile set '{}' is empty", tile_set_name),
+        }
+
+        TileSizeMismatch(
+            tile_set_name: String,
+            tile_index: usize,
+            tiling: Tiling,
+            board: Dimensions,
+            tile_side: SideLength) {
+            description("tile size does not match the board"),
+            display("tile #{} of tile set '{}' is {2}x{2} but a {3} tiling of a {4} board needs a side of {5}",
+                    tile_index, tile_set_name, tile_side, tiling, board,
+                    tiling.expected_side(board)),
+        }
+
+        InvalidPieceFace(tile_set_name: String, piece_index: usize, face: usize) {
+            description("piece face is not a tile"),
+            display("piece #{} of tile set '{}' has a face #{} but there is no such tile",
+                    piece_index, tile_set_name, face),
+        }
+
+        NotEnoughPieces(tile_set_name: String, needed: usize, available: usize) {
+            description("not enough pieces to build a board"),
+            display("tile set '{}' has {} piece(s) but a board needs {}",
+                    tile_set_name, available, needed),
+        }
+
+        NotEnoughColors(tile_set_name: String, needed: usize, available: usize) {
+            description("not enough piece colors to build a board"),
+            display("tile set '{}' has pieces of {} different color(s) but a board needs {}",
+                    tile_set_name, available, needed),
+        }
+
+        OutOfTilePosition(tile_set_name: String, tile_index: usize, pos: LogicalPos, tile_side: SideLength) {
+            description("out-of-tile position"),
+            display("tile #{} of tile set '{}' has a wall or cell at {:?}, out of its {3}x{3} cells",
+                    tile_index, tile_set_name, pos, tile_side),
+        }
+
+        NoTileSet {
+            description("no tile set to build a board"),
+            display("no tile set to build a board"),
         }
     }
 }
@@ -0,0 +1,383 @@
+use serde_derive::Deserialize;
+
+use rand::Rng;
+
+use crate::positionning::{LogicalPos, Dimensions, RotateAngle};
+use crate::wall::{Wall, Side};
+use crate::board::EditableBoard;
+use crate::game::{RobotId, Target};
+
+use super::error::*;
+
+
+// Tries to place a feature before giving up on it, rules being too strict
+// for the board.
+const ATTEMPTS_PER_FEATURE: usize = 50;
+
+const ROBOTS: [RobotId; 4] = [RobotId::Red, RobotId::Green, RobotId::Blue, RobotId::Yellow];
+
+
+/// Rules to lay walls out on a board, instead of using tiles.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Generator {
+    /// L-shaped corners per cell of the board, each of them being a target.
+    #[serde(default = "defaults::density")]
+    pub density: f32,
+    /// Walls sticking out of each edge of the board.
+    #[serde(default = "defaults::stubs_per_edge")]
+    pub stubs_per_edge: usize,
+    /// Side of the forbidden block at the center, none when 0.
+    #[serde(default = "defaults::center_block")]
+    pub center_block: usize,
+    /// Free cells kept around walls, in every direction.
+    #[serde(default = "defaults::spacing")]
+    pub spacing: usize,
+}
+
+
+impl Default for Generator {
+    fn default() -> Generator {
+        Generator {
+            density: 0.066,
+            stubs_per_edge: 2,
+            center_block: 2,
+            spacing: 1,
+        }
+    }
+}
+
+
+mod defaults {
+    use super::Generator;
+
+    pub fn density() -> f32 { Generator::default().density }
+    pub fn stubs_per_edge() -> usize { Generator::default().stubs_per_edge }
+    pub fn center_block() -> usize { Generator::default().center_block }
+    pub fn spacing() -> usize { Generator::default().spacing }
+}
+
+
+// Board being generated, before it is put on an actual one.
+struct Layout {
+    dimensions: Dimensions,
+    walls: Vec<Wall>,
+    forbidden: Vec<LogicalPos>,
+    // Cells holding a wall or forbidden, kept apart by the spacing rule.
+    occupied: Vec<LogicalPos>,
+}
+
+
+impl Generator {
+    /// Put random walls on `board`, every free cell staying reachable from
+    /// the others, returning targets of the board.
+    pub fn build_rand<T, R>(&self, board: &mut T, rng: &mut R) -> Result<Vec<Target>>
+        where T: AsMut<dyn EditableBoard>,
+              R: Rng
+    {
+        let board = board.as_mut();
+        let (layout, targets) = self.lay_out(board.dimensions(), rng);
+
+        for wall in layout.walls.iter() {
+            board.put_wall(wall)?;
+        }
+        for pos in layout.forbidden.iter() {
+            board.forbid_cell(pos)?;
+        }
+
+        Ok(targets)
+    }
+
+
+    fn lay_out<R>(&self, dimensions: Dimensions, rng: &mut R) -> (Layout, Vec<Target>)
+        where R: Rng
+    {
+        let mut layout = Layout {
+            dimensions,
+            walls: Vec::new(),
+            forbidden: Vec::new(),
+            occupied: Vec::new(),
+        };
+
+        self.put_center_block(&mut layout);
+        self.put_edge_stubs(&mut layout, rng);
+        let targets = self.put_corners(&mut layout, rng);
+        (layout, targets)
+    }
+
+
+    fn put_center_block(&self, layout: &mut Layout) {
+        let Dimensions { width, height } = layout.dimensions;
+        let side = self.center_block;
+        // Leave room for robots to go around.
+        if side == 0 || side + 2 > width || side + 2 > height {
+            return;
+        }
+
+        let left = (width - side) / 2;
+        let top = (height - side) / 2;
+        for y in top..top + side {
+            for x in left..left + side {
+                layout.forbidden.push(LogicalPos { x, y });
+                layout.occupied.push(LogicalPos { x, y });
+            }
+        }
+    }
+
+
+    // Walls across the edges of the board, away from its corners.
+    fn put_edge_stubs<R>(&self, layout: &mut Layout, rng: &mut R) where R: Rng {
+        let Dimensions { width, height } = layout.dimensions;
+
+        for edge in [Side::Up, Side::Down, Side::Left, Side::Right].iter() {
+            let length = match edge {
+                Side::Up | Side::Down => width,
+                Side::Left | Side::Right => height,
+            };
+            if length < 4 {
+                continue;
+            }
+
+            for _ in 0..self.stubs_per_edge {
+                for _ in 0..ATTEMPTS_PER_FEATURE {
+                    // Between the second and the second to last cells, the
+                    // wall closing the right or bottom side of the cell.
+                    let along = rng.gen_range(1, length - 1);
+                    let (pos, side) = match edge {
+                        Side::Up => (LogicalPos { x: along, y: 0 }, Side::Right),
+                        Side::Down => (LogicalPos { x: along, y: height - 1 }, Side::Right),
+                        Side::Left => (LogicalPos { x: 0, y: along }, Side::Down),
+                        Side::Right => (LogicalPos { x: width - 1, y: along }, Side::Down),
+                    };
+
+                    if layout.try_put(&pos, vec![Wall { pos, side }], self.spacing) {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+
+    // Pairs of walls making an L in a cell, where robots stop whatever the
+    // way they come from, hence targets.
+    fn put_corners<R>(&self, layout: &mut Layout, rng: &mut R) -> Vec<Target>
+        where R: Rng
+    {
+        let Dimensions { width, height } = layout.dimensions;
+        let mut targets = Vec::new();
+        if width < 3 || height < 3 {
+            return targets;
+        }
+
+        let count = (width as f32 * height as f32 * self.density.max(0f32)).round() as usize;
+        let mut attempts = 0;
+
+        while targets.len() < count && attempts < count * ATTEMPTS_PER_FEATURE {
+            attempts += 1;
+
+            // Out of the edges, stubs being there already.
+            let pos = LogicalPos {
+                x: rng.gen_range(1, width - 1),
+                y: rng.gen_range(1, height - 1),
+            };
+            let vertical = if rng.gen() { Side::Left } else { Side::Right };
+            let horizontal = if rng.gen() { Side::Up } else { Side::Down };
+            let walls = vec![
+                Wall { pos, side: vertical },
+                Wall { pos, side: horizontal },
+            ];
+
+            if layout.try_put(&pos, walls, self.spacing) {
+                let robot = ROBOTS[targets.len() % ROBOTS.len()];
+                targets.push(Target::new(robot, pos));
+            }
+        }
+
+        targets
+    }
+}
+
+
+impl Layout {
+    // Add `walls` around `pos` if they are far enough from other ones and
+    // do not split the board.
+    fn try_put(&mut self, pos: &LogicalPos, walls: Vec<Wall>, spacing: usize) -> bool {
+        let too_close = self.occupied.iter().any(|other| {
+            pos.x.abs_diff(other.x) <= spacing && pos.y.abs_diff(other.y) <= spacing
+        });
+        if too_close {
+            return false;
+        }
+
+        let count = self.walls.len();
+        self.walls.extend(walls);
+        if !self.is_connected() {
+            self.walls.truncate(count);
+            return false;
+        }
+
+        self.occupied.push(*pos);
+        true
+    }
+
+
+    // Whether every free cell can be reached from any other one, walking
+    // cell by cell.
+    fn is_connected(&self) -> bool {
+        let Dimensions { width, height } = self.dimensions;
+        let is_free = |pos: &LogicalPos| !self.forbidden.contains(pos);
+
+        let free_count = (0..width * height)
+            .filter(|i| is_free(&LogicalPos { x: i % width, y: i / width }))
+            .count();
+        let start = match (0..width * height)
+            .map(|i| LogicalPos { x: i % width, y: i / width })
+            .find(|pos| is_free(pos)) {
+            Some(start) => start,
+            None => return true,
+        };
+
+        let mut visited = vec![false; width * height];
+        visited[start.y * width + start.x] = true;
+        let mut pending = vec![start];
+        let mut count = 1;
+
+        while let Some(pos) = pending.pop() {
+            for side in [Side::Up, Side::Down, Side::Left, Side::Right].iter() {
+                let next = match self.neighbour(&pos, *side) {
+                    Some(next) => next,
+                    None => continue,
+                };
+                let index = next.y * width + next.x;
+                if visited[index] || !is_free(&next) || self.is_blocked(&pos, &next, *side) {
+                    continue;
+                }
+
+                visited[index] = true;
+                count += 1;
+                pending.push(next);
+            }
+        }
+
+        count == free_count
+    }
+
+
+    fn neighbour(&self, pos: &LogicalPos, side: Side) -> Option<LogicalPos> {
+        match side {
+            Side::Up if pos.y > 0 => Some(LogicalPos { y: pos.y - 1, ..*pos }),
+            Side::Down if pos.y + 1 < self.dimensions.height => Some(LogicalPos { y: pos.y + 1, ..*pos }),
+            Side::Left if pos.x > 0 => Some(LogicalPos { x: pos.x - 1, ..*pos }),
+            Side::Right if pos.x + 1 < self.dimensions.width => Some(LogicalPos { x: pos.x + 1, ..*pos }),
+            _ => None,
+        }
+    }
+
+
+    // A wall between two cells may be given on either of them.
+    fn is_blocked(&self, pos: &LogicalPos, next: &LogicalPos, side: Side) -> bool {
+        let back = side.rotate(RotateAngle::HalfTurn);
+        self.walls.iter().any(|wall| {
+            (wall.pos == *pos && wall.side == side) || (wall.pos == *next && wall.side == back)
+        })
+    }
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    use crate::positionning::{Dimensions, LogicalPos};
+    use crate::wall::{Side, Wall};
+
+    use super::{Generator, Layout};
+
+
+    const SEEDS: u64 = 20;
+
+
+    fn board() -> Dimensions {
+        Dimensions { width: 16, height: 16 }
+    }
+
+
+    fn lay_out(generator: &Generator, dimensions: Dimensions, seed: u64) -> Layout {
+        generator.lay_out(dimensions, &mut StdRng::seed_from_u64(seed)).0
+    }
+
+
+    fn is_on_edge(pos: &LogicalPos, dimensions: &Dimensions) -> bool {
+        pos.x == 0 || pos.y == 0 || pos.x + 1 == dimensions.width || pos.y + 1 == dimensions.height
+    }
+
+
+    #[test]
+    fn generated_boards_are_connected() {
+        for dimensions in [board(), Dimensions { width: 12, height: 9 }].iter() {
+            for seed in 0..SEEDS {
+                let layout = lay_out(&Generator::default(), *dimensions, seed);
+                assert!(!layout.walls.is_empty());
+                assert!(layout.is_connected(), "board of seed {} is split", seed);
+            }
+        }
+    }
+
+
+    #[test]
+    fn walls_closing_a_cell_split_the_board() {
+        let mut layout = lay_out(&Generator { density: 0.0, stubs_per_edge: 0, ..Generator::default() },
+                                 board(), 0);
+        assert!(layout.is_connected());
+
+        let corner = LogicalPos { x: 0, y: 0 };
+        layout.walls.push(Wall { pos: corner, side: Side::Right });
+        layout.walls.push(Wall { pos: LogicalPos { x: 0, y: 1 }, side: Side::Up });
+        assert!(!layout.is_connected());
+    }
+
+
+    #[test]
+    fn stubs_stick_out_of_edges_away_from_corners() {
+        let dimensions = board();
+        for seed in 0..SEEDS {
+            let layout = lay_out(&Generator::default(), dimensions, seed);
+            let stubs: Vec<&Wall> = layout.walls.iter()
+                .filter(|wall| is_on_edge(&wall.pos, &dimensions))
+                .collect();
+            assert_eq!(stubs.len(), 4 * Generator::default().stubs_per_edge, "seed {}", seed);
+
+            for wall in stubs {
+                let LogicalPos { x, y } = wall.pos;
+                if y == 0 || y + 1 == dimensions.height {
+                    assert_eq!(wall.side, Side::Right);
+                    assert!((1..dimensions.width - 1).contains(&x), "stub at {:?}", wall.pos);
+                }
+                else {
+                    assert_eq!(wall.side, Side::Down);
+                    assert!((1..dimensions.height - 1).contains(&y), "stub at {:?}", wall.pos);
+                }
+            }
+        }
+    }
+
+
+    #[test]
+    fn center_block_is_forbidden() {
+        let layout = lay_out(&Generator::default(), board(), 0);
+        let mut forbidden = layout.forbidden.clone();
+        forbidden.sort_by_key(|pos| (pos.y, pos.x));
+        assert_eq!(forbidden, vec![
+            LogicalPos { x: 7, y: 7 },
+            LogicalPos { x: 8, y: 7 },
+            LogicalPos { x: 7, y: 8 },
+            LogicalPos { x: 8, y: 8 },
+        ]);
+        assert!(layout.walls.iter().all(|wall| !forbidden.contains(&wall.pos)));
+
+        let without = lay_out(&Generator { center_block: 0, ..Generator::default() }, board(), 0);
+        assert!(without.forbidden.is_empty());
+    }
+}
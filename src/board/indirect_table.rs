@@ -1,4 +1,4 @@
-use crate::positionning::{LogicalPos, PosExtra, Way, Hit, SideLength};
+use crate::positionning::{LogicalPos, PosExtra, Way, Hit, Dimensions};
 use crate::moves::MovePossibility;
 use crate::wall::{Wall, Side};
 
@@ -71,8 +71,8 @@ impl BoardByIndirectTable {
 
 
 impl Board for BoardByIndirectTable {
-    fn side_length(&self) -> SideLength {
-        SideLength(self.row_count())
+    fn dimensions(&self) -> Dimensions {
+        Dimensions { width: self.column_count(), height: self.row_count() }
     }
 
     fn is_start_pos(&self, _pos: &LogicalPos) -> Result<bool> {
@@ -147,11 +147,10 @@ impl Board for BoardByIndirectTable {
 
 impl EditableBoard for BoardByIndirectTable {
 
-    fn reset(&mut self, side_length: &SideLength) -> Result<()> {
-        let side = side_length.0;
-        if side >= 2 {
-            self.walls_to_move_on_x = (0..side).map(|_| Vec::new()).collect();
-            self.walls_to_move_on_y = (0..side).map(|_| Vec::new()).collect();
+    fn reset(&mut self, dimensions: &Dimensions) -> Result<()> {
+        if dimensions.width >= 2 && dimensions.height >= 2 {
+            self.walls_to_move_on_x = (0..dimensions.height).map(|_| Vec::new()).collect();
+            self.walls_to_move_on_y = (0..dimensions.width).map(|_| Vec::new()).collect();
             Ok(())
         }
         else {
@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 
-use crate::positionning::{LogicalPos, PosExtra, Way, Hit, SideLength};
+use crate::positionning::{LogicalPos, PosExtra, Way, Hit, Dimensions};
 use crate::moves::MovePossibility;
 use crate::wall::{Wall, Side};
 
@@ -10,7 +10,7 @@ use super::board::{Board, EditableBoard};
 
 #[derive(Debug)]
 pub struct BoardByIndividualCells {
-    side_length: SideLength,
+    dimensions: Dimensions,
     cells: HashMap<LogicalPos, MovePossibility>,
 }
 
@@ -20,7 +20,7 @@ impl BoardByIndividualCells {
     #[allow(dead_code)]
     pub fn new() -> BoardByIndividualCells {
         BoardByIndividualCells {
-            side_length: SideLength(0),
+            dimensions: Dimensions { width: 0, height: 0 },
             cells: HashMap::new(),
         }
     }
@@ -29,8 +29,8 @@ impl BoardByIndividualCells {
 
 
 impl Board for BoardByIndividualCells {
-    fn side_length(&self) -> SideLength {
-        self.side_length.clone()
+    fn dimensions(&self) -> Dimensions {
+        self.dimensions
     }
 
 
@@ -48,9 +48,9 @@ impl Board for BoardByIndividualCells {
                     .unwrap_or_else(MovePossibility::all);
 
                 moves.up &= start.y > 0;
-                moves.down &= start.y + 1 < self.side_length.0;
+                moves.down &= start.y + 1 < self.dimensions.height;
                 moves.left &= start.x > 0;
-                moves.right &= start.x + 1 < self.side_length.0;
+                moves.right &= start.x + 1 < self.dimensions.width;
                 
                 moves.forbidden = !(moves.left | moves.right | moves.up | moves.down);
 
@@ -89,10 +89,10 @@ impl Board for BoardByIndividualCells {
 
 impl EditableBoard for BoardByIndividualCells {
 
-    fn reset(&mut self, side_length: &SideLength) -> Result<()> {
-        if side_length.0 >= 2 && side_length.0 >= 2 {
+    fn reset(&mut self, dimensions: &Dimensions) -> Result<()> {
+        if dimensions.width >= 2 && dimensions.height >= 2 {
             self.cells.clear();
-            self.side_length = side_length.clone();
+            self.dimensions = *dimensions;
             Ok(())
         }
         else {
@@ -119,7 +119,7 @@ impl EditableBoard for BoardByIndividualCells {
                     }
                 },
                 Side::Down => {
-                    if wall.pos.y + 1 != self.side_length.0
+                    if wall.pos.y + 1 != self.dimensions.height
                     {
                         self.cells
                             .entry(wall.pos.clone())
@@ -147,7 +147,7 @@ impl EditableBoard for BoardByIndividualCells {
                     }
                 },
                 Side::Right => {
-                    if wall.pos.x + 1 != self.side_length.0
+                    if wall.pos.x + 1 != self.dimensions.width
                     {
                         self.cells
                             .entry(wall.pos.clone())
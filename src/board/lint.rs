@@ -0,0 +1,257 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::positionning::LogicalPos;
+use crate::wall::{Wall, Side};
+use crate::game::RobotId;
+
+use super::tile::Tile;
+use super::tile_set::TileSet;
+use super::tiling::Tiling;
+
+
+/// Gameplay issue found in a tile, that does not prevent building boards.
+#[derive(Debug)]
+pub struct LintWarning {
+    pub tile_set: String,
+    pub tile_index: usize,
+    pub pos: Option<LogicalPos>,
+    pub message: String,
+    // Row of the tile in its notation, showing the position.
+    excerpt: Option<String>,
+}
+
+
+impl LintWarning {
+    fn new(tile_set: &TileSet, tile_index: usize, message: String) -> LintWarning {
+        LintWarning {
+            tile_set: tile_set.name.clone(),
+            tile_index,
+            pos: None,
+            message,
+            excerpt: None,
+        }
+    }
+
+    fn at(mut self, tile: &Tile, pos: &LogicalPos) -> LintWarning {
+        self.excerpt = tile.ascii_rows().get(pos.y)
+            .map(|row| format!("{}\n{}^", row, " ".repeat(pos.x * 3)));
+        self.pos = Some(*pos);
+        self
+    }
+}
+
+
+impl fmt::Display for LintWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "tile set '{}', tile #{}", self.tile_set, self.tile_index)?;
+        if let Some(ref pos) = self.pos {
+            // Rows and columns are counted from 1, like when reading a tile.
+            write!(f, ", row {} column {}", pos.y + 1, pos.x + 1)?;
+        }
+        write!(f, ": {}", self.message)?;
+        if let Some(ref excerpt) = self.excerpt {
+            for line in excerpt.lines() {
+                write!(f, "\n    {}", line)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+
+/// Check gameplay invariants of every tile of `tile_set`.
+pub fn lint_tile_set(tile_set: &TileSet) -> Vec<LintWarning> {
+    let mut warnings = Vec::new();
+
+    for (i, tile) in tile_set.tiles.iter().enumerate() {
+        check_enclosed_regions(tile_set, i, tile, &mut warnings);
+        check_center(tile_set, i, tile, &mut warnings);
+        check_redundant_walls(tile_set, i, tile, &mut warnings);
+    }
+    check_target_counts(tile_set, &mut warnings);
+
+    warnings
+}
+
+
+// Open cells a robot cannot get to from the rest of the board.
+fn check_enclosed_regions(
+    tile_set: &TileSet,
+    index: usize,
+    tile: &Tile,
+    warnings: &mut Vec<LintWarning>) {
+    let side = tile.side_length().0;
+    let is_open = |pos: &LogicalPos| !tile.forbidden().contains(pos);
+    let blocked = |pos: &LogicalPos, side: Side| tile.walls().contains(&Wall { pos: *pos, side });
+
+    // Group open cells by region, walls and forbidden cells splitting them.
+    let mut regions: Vec<Vec<LogicalPos>> = Vec::new();
+    let mut region_of = vec![None; side * side];
+
+    for start in (0..side * side).map(|i| LogicalPos { x: i % side, y: i / side }) {
+        if !is_open(&start) || region_of[start.y * side + start.x].is_some() {
+            continue;
+        }
+
+        let region = regions.len();
+        let mut cells = vec![start];
+        let mut pending = vec![start];
+        region_of[start.y * side + start.x] = Some(region);
+
+        while let Some(pos) = pending.pop() {
+            let mut neighbours = Vec::new();
+            if pos.x + 1 < side && !blocked(&pos, Side::Right) {
+                neighbours.push(LogicalPos { x: pos.x + 1, ..pos });
+            }
+            if pos.y + 1 < side && !blocked(&pos, Side::Down) {
+                neighbours.push(LogicalPos { y: pos.y + 1, ..pos });
+            }
+            if pos.x > 0 && !blocked(&LogicalPos { x: pos.x - 1, ..pos }, Side::Right) {
+                neighbours.push(LogicalPos { x: pos.x - 1, ..pos });
+            }
+            if pos.y > 0 && !blocked(&LogicalPos { y: pos.y - 1, ..pos }, Side::Down) {
+                neighbours.push(LogicalPos { y: pos.y - 1, ..pos });
+            }
+
+            for next in neighbours.into_iter().filter(|next| is_open(next)) {
+                let cell = &mut region_of[next.y * side + next.x];
+                if cell.is_none() {
+                    *cell = Some(region);
+                    cells.push(next);
+                    pending.push(next);
+                }
+            }
+        }
+
+        regions.push(cells);
+    }
+
+    // Quadrants are open toward the center of the board, on their right and
+    // bottom sides, and tiles of a grid on every side. A whole board tile is
+    // only open within its largest region.
+    let open_to_board = |cells: &Vec<LogicalPos>| match tile_set.tiling {
+        Tiling::WholeBoard => false,
+        Tiling::Grid { .. } => cells.iter().any(|pos| {
+            pos.x == 0 || pos.y == 0 || pos.x + 1 == side || pos.y + 1 == side
+        }),
+        _ => cells.iter().any(|pos| pos.x + 1 == side || pos.y + 1 == side),
+    };
+    let largest = regions.iter()
+        .enumerate()
+        .max_by_key(|(_, cells)| cells.len())
+        .map(|(i, _)| i);
+
+    for (i, cells) in regions.iter().enumerate() {
+        let reachable = match tile_set.tiling {
+            Tiling::WholeBoard => Some(i) == largest,
+            _ => open_to_board(cells),
+        };
+        if reachable {
+            continue;
+        }
+
+        let message =
+            if cells.len() == 1 { "cell walled in, robots can never reach it".to_string() }
+            else {
+                format!("region of {} cells enclosed by walls, robots can never reach it",
+                        cells.len())
+            };
+        warnings.push(LintWarning::new(tile_set, index, message).at(tile, &cells[0]));
+    }
+}
+
+
+// Quadrants have a single forbidden cell, in the corner at the center of the
+// board.
+fn check_center(
+    tile_set: &TileSet,
+    index: usize,
+    tile: &Tile,
+    warnings: &mut Vec<LintWarning>) {
+    match tile_set.tiling {
+        Tiling::Quadrants | Tiling::QuadrantsWithGap => {},
+        Tiling::WholeBoard | Tiling::Grid { .. } => return,
+    }
+
+    let side = tile.side_length().0;
+    let center = LogicalPos { x: side - 1, y: side - 1 };
+
+    if !tile.forbidden().contains(&center) {
+        let message = "the center corner should be forbidden ('#')".to_string();
+        warnings.push(LintWarning::new(tile_set, index, message).at(tile, &center));
+    }
+
+    for pos in tile.forbidden().iter().filter(|pos| **pos != center) {
+        let message = "forbidden cell out of the center corner".to_string();
+        warnings.push(LintWarning::new(tile_set, index, message).at(tile, pos));
+    }
+}
+
+
+// Walls given twice, or along a forbidden cell which is already walled in.
+fn check_redundant_walls(
+    tile_set: &TileSet,
+    index: usize,
+    tile: &Tile,
+    warnings: &mut Vec<LintWarning>) {
+    let walls = tile.walls();
+
+    for (i, wall) in walls.iter().enumerate() {
+        let neighbour = match wall.side {
+            Side::Right => LogicalPos { x: wall.pos.x + 1, ..wall.pos },
+            Side::Down => LogicalPos { y: wall.pos.y + 1, ..wall.pos },
+            Side::Left if wall.pos.x > 0 => LogicalPos { x: wall.pos.x - 1, ..wall.pos },
+            Side::Up if wall.pos.y > 0 => LogicalPos { y: wall.pos.y - 1, ..wall.pos },
+            _ => wall.pos,
+        };
+
+        let message =
+            if walls[..i].contains(wall) {
+                Some("wall given twice")
+            }
+            else if tile.forbidden().contains(&wall.pos)
+                || tile.forbidden().contains(&neighbour) {
+                Some("wall along a forbidden cell, which is walled in anyway")
+            }
+            else { None };
+
+        if let Some(message) = message {
+            let warning = LintWarning::new(tile_set, index, message.to_string())
+                .at(tile, &wall.pos);
+            warnings.push(warning);
+        }
+    }
+}
+
+
+// Every tile of a set should give each robot as many targets.
+fn check_target_counts(tile_set: &TileSet, warnings: &mut Vec<LintWarning>) {
+    let counts: Vec<HashMap<RobotId, usize>> = tile_set.tiles.iter()
+        .map(|tile| {
+            let mut counts = HashMap::new();
+            for target in tile.targets() {
+                *counts.entry(target.robot).or_insert(0) += 1;
+            }
+            counts
+        })
+        .collect();
+
+    let reference = match counts.first() {
+        Some(reference) => reference,
+        None => return,
+    };
+
+    for (i, tile_counts) in counts.iter().enumerate().skip(1) {
+        for robot in [RobotId::Red, RobotId::Green, RobotId::Blue, RobotId::Yellow].iter() {
+            let expected = reference.get(robot).cloned().unwrap_or(0);
+            let actual = tile_counts.get(robot).cloned().unwrap_or(0);
+            if expected != actual {
+                let message = format!(
+                    "{} target(s) of the {:?} robot, while tile #0 has {}",
+                    actual, robot, expected);
+                warnings.push(LintWarning::new(tile_set, i, message));
+            }
+        }
+    }
+}
@@ -3,16 +3,25 @@ pub mod error;
 mod board;
 mod tile;
 mod tile_set;
+mod tiling;
+mod piece;
+mod generator;
 mod border;
 mod tile_parser;
 mod tile_parser_error;
 mod builder;
+mod snapshot;
+mod lint;
 
 pub use self::board::{Board, EditableBoard};
 pub use self::tile::Tile;
 pub use self::tile_set::TileSet;
+pub use self::tiling::Tiling;
 pub use self::border::Border;
-pub use self::builder::Builder;
+pub use self::builder::{Builder, BoardSource};
+pub use self::generator::Generator;
+pub use self::snapshot::BoardSnapshot;
+pub use self::lint::lint_tile_set;
 
 
 // Boards implementation
@@ -0,0 +1,23 @@
+use serde_derive::Deserialize;
+
+use crate::game::RobotId;
+
+
+/// Physical quadrant of the game: a board printed on both faces, whose color
+/// tells which of the four pieces of a game it is.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Piece {
+    /// Pieces of a board all have different colors, when they have one.
+    #[serde(default)]
+    pub color: Option<RobotId>,
+    /// Indices of the tiles printed on each face.
+    pub faces: Vec<usize>,
+}
+
+
+impl Piece {
+    // Piece with a single face, for tile sets without pieces.
+    pub fn single_face(tile_index: usize) -> Piece {
+        Piece { color: None, faces: vec![tile_index] }
+    }
+}
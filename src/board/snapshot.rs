@@ -0,0 +1,54 @@
+use serde_derive::{Serialize, Deserialize};
+
+use crate::positionning::{LogicalPos, Dimensions};
+use crate::wall::{Wall, Side};
+
+use super::error::*;
+use super::board::{Board, EditableBoard};
+
+
+/// Plain description of a board content, independent of its implementation.
+///
+/// Only walls on the right and at the bottom of a cell are kept, forbidden
+/// cells being nothing more than cells surrounded by walls.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoardSnapshot {
+    pub dimensions: Dimensions,
+    pub walls: Vec<Wall>,
+}
+
+
+impl BoardSnapshot {
+    pub fn from_board(board: &dyn Board) -> Result<BoardSnapshot> {
+        let dimensions = board.dimensions();
+        let mut walls = Vec::new();
+
+        for y in 0..dimensions.height {
+            for x in 0..dimensions.width {
+                let pos = LogicalPos{ x, y };
+                let moves = board.moves_from(&pos)?;
+
+                if !moves.right && x + 1 < dimensions.width {
+                    walls.push(Wall{ pos, side: Side::Right });
+                }
+
+                if !moves.down && y + 1 < dimensions.height {
+                    walls.push(Wall{ pos, side: Side::Down });
+                }
+            }
+        }
+
+        Ok(BoardSnapshot { dimensions, walls })
+    }
+
+
+    pub fn apply_on(&self, board: &mut dyn EditableBoard) -> Result<()> {
+        board.reset(&self.dimensions)?;
+
+        for wall in self.walls.iter() {
+            board.put_wall(wall)?;
+        }
+
+        Ok(())
+    }
+}
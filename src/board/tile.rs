@@ -1,84 +1,192 @@
 use crate::wall::{Wall, Side};
 
-use crate::positionning::{LogicalPos, RotateAngle, SideLength};
+use crate::positionning::{LogicalPos, SideLength};
+use crate::game::Target;
 
 use super::error::*;
 use super::board::EditableBoard;
-use super::border::Border;
+use super::tiling::Placement;
+use super::tile_parser::TileParser;
 
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct Tile{
+    side_length: SideLength,
     walls: Vec<Wall>, 
-    forbidden: Vec<LogicalPos>
+    forbidden: Vec<LogicalPos>,
+    targets: Vec<Target>,
 }
 
 
 impl Tile {
     // NOTE: By default, tile are considered to be `Border::TopLeft`.
 
-    pub fn new(walls: Vec<Wall>, forbidden: Vec<LogicalPos>) -> Tile {
-        Tile { walls, forbidden }
+    pub fn new(side_length: SideLength, walls: Vec<Wall>, forbidden: Vec<LogicalPos>) -> Tile {
+        Tile { side_length, walls, forbidden, targets: Vec::new() }
     }
 
-    pub fn apply_on<T>(&self, board: &mut T, border: &Border) -> Result<()>
+    pub fn with_targets(mut self, targets: Vec<Target>) -> Tile {
+        self.targets = targets;
+        self
+    }
+
+    pub fn side_length(&self) -> &SideLength {
+        &self.side_length
+    }
+
+    pub fn walls(&self) -> &[Wall] {
+        &self.walls
+    }
+
+    pub fn forbidden(&self) -> &[LogicalPos] {
+        &self.forbidden
+    }
+
+    pub fn targets(&self) -> &[Target] {
+        &self.targets
+    }
+
+    // Rows of the tile in the notation of config files: each cell takes three
+    // characters, its content then its bottom and right walls.
+    pub fn ascii_rows(&self) -> Vec<String> {
+        let side = self.side_length.0;
+        let has_wall = |x, y, side: Side| self.walls.iter()
+            .any(|wall| wall.pos == LogicalPos { x, y } && wall.side == side);
+
+        (0..side)
+            .map(|y| (0..side)
+                .map(|x| {
+                    let pos = LogicalPos { x, y };
+                    let content =
+                        if self.forbidden.contains(&pos) { '#' }
+                        else {
+                            self.targets.iter()
+                                .find(|target| target.pos == pos)
+                                .map_or('.', |target| TileParser::letter_of(target.robot))
+                        };
+                    let down = if has_wall(x, y, Side::Down) { '_' } else { ' ' };
+                    let right = if has_wall(x, y, Side::Right) { '|' } else { ' ' };
+                    format!("{}{}{}", content, down, right)
+                })
+                .collect())
+            .collect()
+    }
+
+    // First wall or forbidden cell out of the tile, if any.
+    pub fn find_out_of_tile(&self) -> Option<LogicalPos> {
+        let side = self.side_length.0;
+        self.walls.iter()
+            .map(|wall| &wall.pos)
+            .chain(self.forbidden.iter())
+            .find(|pos| pos.x >= side || pos.y >= side)
+            .cloned()
+    }
+
+    pub fn apply_on<T>(&self, board: &mut T, placement: &Placement) -> Result<()>
         where T: AsMut<dyn EditableBoard>
     {
         let board = board.as_mut();
 
         for wall in self.walls.iter() {
-            let wall = Self::situate_on_board(
-                wall, 
-                border, 
-                &board.side_length());
-            
-            board.put_wall(&wall)
-                .expect("board can put a wall at given position");
+            let wall = self.situate_on_board(wall, placement);
+            board.put_wall(&wall)?;
         }
 
         for pos in self.forbidden.iter() {
-            let correct_pos = Self::situate_on_board(
-                &Wall { pos: pos.clone(), side: Side::Left }, 
-                border, 
-                &board.side_length()).pos;
-            
-            board.forbid_cell(&correct_pos)
-                .expect("board can forbid cell at given position");
+            let correct_pos = placement.situate(pos, &self.side_length);
+            board.forbid_cell(&correct_pos)?;
         }
 
         Ok(())
     }
 
-    fn situate_on_board(
-        wall: &Wall,
-        border: &Border,
-        board_side: &SideLength) -> Wall {
-        match *border {
-            Border::TopLeft => wall.clone(),
-            Border::TopRight => {
-                let pos = LogicalPos{
-                    x: board_side.0 - wall.pos.y - 1,
-                    y: wall.pos.x
-                };
-                let side = wall.side.rotate(RotateAngle::TurnRight);
-                Wall{ pos, side }
-            },
-            Border::BottomLeft => {
-                let pos = LogicalPos{
-                    x: board_side.0 - wall.pos.x - 1,
-                    y: board_side.0 - wall.pos.y - 1
-                };
-                let side = wall.side.rotate(RotateAngle::HalfTurn);
-                Wall{ pos, side }
-            },
-            Border::BottomRight => {
-                let pos = LogicalPos{
-                    x: wall.pos.y,
-                    y: board_side.0 - wall.pos.x - 1
-                };
-                let side = wall.side.rotate(RotateAngle::TurnLeft);
-                Wall{ pos, side }
-            },
+    pub fn situate_targets(&self, placement: &Placement) -> Vec<Target> {
+        self.targets.iter()
+            .map(|target| Target::new(
+                    target.robot,
+                    placement.situate(&target.pos, &self.side_length)))
+            .collect()
+    }
+
+    fn situate_on_board(&self, wall: &Wall, placement: &Placement) -> Wall {
+        let pos = placement.situate(&wall.pos, &self.side_length);
+        let side = wall.side.flip(placement.flip).rotate(placement.angle());
+        Wall{ pos, side }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::positionning::{FlipAxis, LogicalPos, SideLength};
+    use crate::wall::{Side, Wall};
+
+    use super::super::tiling::Placement;
+    use super::Tile;
+
+
+    const SIDES: [Side; 4] = [Side::Up, Side::Down, Side::Left, Side::Right];
+    const FLIPS: [FlipAxis; 4] = [
+        FlipAxis::NoFlip,
+        FlipAxis::FlipHorizontal,
+        FlipAxis::FlipVertical,
+        FlipAxis::FlipBoth,
+    ];
+
+
+    // Cell on the other side of `side` of `pos`, which is not on an edge.
+    fn beyond(pos: &LogicalPos, side: Side) -> LogicalPos {
+        match side {
+            Side::Up => LogicalPos { y: pos.y - 1, ..*pos },
+            Side::Down => LogicalPos { y: pos.y + 1, ..*pos },
+            Side::Left => LogicalPos { x: pos.x - 1, ..*pos },
+            Side::Right => LogicalPos { x: pos.x + 1, ..*pos },
+        }
+    }
+
+
+    #[test]
+    fn walls_keep_separating_the_same_cells() {
+        let side_length = SideLength(3);
+        let tile = Tile::new(side_length.clone(), Vec::new(), Vec::new());
+        let center = LogicalPos { x: 1, y: 1 };
+
+        // On a square board, then as the second tile of a 2x1 grid.
+        for origin in [LogicalPos { x: 0, y: 0 }, LogicalPos { x: 3, y: 0 }].iter() {
+            for &flip in FLIPS.iter() {
+                for quarter_turns in 0..4 {
+                    let placement = Placement { origin: *origin, quarter_turns, flip };
+                    for &side in SIDES.iter() {
+                        let wall = tile.situate_on_board(&Wall { pos: center, side }, &placement);
+                        assert_eq!(wall.pos, placement.situate(&center, &side_length));
+                        assert_eq!(beyond(&wall.pos, wall.side),
+                                   placement.situate(&beyond(&center, side), &side_length),
+                                   "{:?} wall with {}", side, placement);
+                    }
+                }
+            }
+        }
+    }
+
+
+    #[test]
+    fn walls_turn_and_flip_with_their_tile() {
+        let side_length = SideLength(3);
+        let tile = Tile::new(side_length, Vec::new(), Vec::new());
+        let wall = Wall { pos: LogicalPos { x: 0, y: 0 }, side: Side::Right };
+        let expected = [
+            (FlipAxis::NoFlip, [Side::Right, Side::Down, Side::Left, Side::Up]),
+            (FlipAxis::FlipHorizontal, [Side::Left, Side::Up, Side::Right, Side::Down]),
+            (FlipAxis::FlipVertical, [Side::Right, Side::Down, Side::Left, Side::Up]),
+            (FlipAxis::FlipBoth, [Side::Left, Side::Up, Side::Right, Side::Down]),
+        ];
+
+        for &(flip, sides) in expected.iter() {
+            for (quarter_turns, &side) in sides.iter().enumerate() {
+                let placement = Placement { origin: LogicalPos { x: 0, y: 0 }, quarter_turns, flip };
+                assert_eq!(tile.situate_on_board(&wall, &placement).side, side,
+                           "{}", placement);
+            }
         }
     }
 }
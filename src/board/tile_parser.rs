@@ -1,5 +1,6 @@
 use crate::positionning::{LogicalPos, SideLength};
 use crate::wall::{Wall, Side};
+use crate::game::{Target, RobotId};
 
 use super::tile_parser_error::*;
 use super::tile::Tile;
@@ -21,16 +22,18 @@ impl<'a> TileParser<'a> {
 
         let mut walls = Vec::new();
         let mut forbidden = Vec::new();
+        let mut targets = Vec::new();
         let mut row = 0;
         let mut column = 0;
                                 
         let expected_cells = vec![
             "free cell".into(), 
-            "forbidden cell".into()];
+            "forbidden cell".into(),
+            "target cell".into()];
 
         for item in self.text.as_bytes() {
             match *item as char {
-                '#' | '.' => {
+                '#' | '.' | 'r' | 'g' | 'b' | 'y' => {
                     if row >= side {
                         bail!(ErrorKind::TooLargeContent(side, self.text.clone()));
                     }
@@ -41,9 +44,15 @@ impl<'a> TileParser<'a> {
                         row += 1;
                     }
 
-                    if *item as char == '#' {
-                        let pos = LogicalPos{ x: column - 1, y: row };
-                        forbidden.push(pos);
+                    let pos = LogicalPos{ x: column - 1, y: row };
+                    match *item as char {
+                        '#' => forbidden.push(pos),
+                        '.' => {},
+                        letter => {
+                            let robot = Self::robot_of(letter)
+                                .expect("target letters are known");
+                            targets.push(Target::new(robot, pos));
+                        },
                     }
                 },
                 '|' => {
@@ -72,6 +81,7 @@ impl<'a> TileParser<'a> {
                 unexpected => {
                     let expected = vec![
                         "cell".into(),
+                        "target".into(),
                         "vertical wall".into(),
                         "horizontal wall".into()];
 
@@ -83,7 +93,7 @@ impl<'a> TileParser<'a> {
         }
 
         if row + 1 == side && column == side {
-            Ok(Tile::new(walls, forbidden))
+            Ok(Tile::new(side_length.clone(), walls, forbidden).with_targets(targets))
         }
         // missing some rows
         else if row < side {
@@ -94,4 +104,27 @@ impl<'a> TileParser<'a> {
             unreachable!("too large content error should have been returned earlier");
         }
     }
+
+
+    /// Robot of a target cell, written with the first letter of its color.
+    pub fn robot_of(letter: char) -> Option<RobotId> {
+        match letter {
+            'r' => Some(RobotId::Red),
+            'g' => Some(RobotId::Green),
+            'b' => Some(RobotId::Blue),
+            'y' => Some(RobotId::Yellow),
+            _ => None,
+        }
+    }
+
+
+    /// Letter of a target cell, the reverse of `robot_of`.
+    pub fn letter_of(robot: RobotId) -> char {
+        match robot {
+            RobotId::Red => 'r',
+            RobotId::Green => 'g',
+            RobotId::Blue => 'b',
+            RobotId::Yellow => 'y',
+        }
+    }
 }
@@ -1,21 +1,34 @@
 use serde_derive::Deserialize;
 
 use rand::Rng;
+use rand::seq::SliceRandom;
 
-use crate::positionning::SideLength;
-use crate::board::{EditableBoard, Border};
+use crate::positionning::{SideLength, Dimensions};
+use crate::board::EditableBoard;
+use crate::game::{RobotId, Target};
 
 use super::error::*;
 use super::tile_parser::TileParser;
 use super::tile::Tile;
+use super::tiling::Tiling;
+use super::piece::Piece;
 
 
 #[derive(Debug, Deserialize)]
 pub struct TileSet {
     pub side_length: SideLength,
     pub name: String,
+    #[serde(default)]
+    pub tiling: Tiling,
+    /// Tiles may be mirrored, in addition to being turned.
+    #[serde(default)]
+    pub mirror: bool,
     #[serde(rename = "sets")]
     raw_tiles: Vec<String>,
+    /// How tiles are printed on physical pieces, each tile being a piece of
+    /// its own when empty.
+    #[serde(default)]
+    pieces: Vec<Piece>,
     #[serde(default, skip)]
     pub tiles: Vec<Tile>
 }
@@ -33,30 +46,168 @@ impl TileSet {
                      err.chain_err(|| ErrorKind::InvalidTileFormat(self.name.clone(), i))
                  }))
             .collect::<std::result::Result<Vec<_>, _>>()?;
-        
-        println!("tiles {:?}", self.tiles);
-        
+
         Ok(())
     }
 
 
-    pub fn build_rand<T>(&self, board: &mut T) -> Result<()>
-        where T: AsMut<dyn EditableBoard>
-    {
-        let mut rng = rand::thread_rng();
-
+    /// Check tiles can be laid out on a board of `board_side`, so that
+    /// building it cannot fail.
+    pub fn validate(&self, board: &Dimensions) -> Result<()> {
         if self.tiles.is_empty() {
-            bail!(ErrorKind::EmptyTileSet);
+            bail!(ErrorKind::EmptyTileSet(self.name.clone()));
         }
 
-        for border in Border::all() {
-            let i = rng.gen_range(0, self.tiles.len());
+        for (i, piece) in self.pieces.iter().enumerate() {
+            if let Some(&face) = piece.faces.iter().find(|&&face| face >= self.tiles.len()) {
+                bail!(ErrorKind::InvalidPieceFace(self.name.clone(), i, face));
+            }
+            if piece.faces.is_empty() {
+                bail!(ErrorKind::InvalidPieceFace(self.name.clone(), i, 0));
+            }
+        }
 
-            println!("Put tile '{}':{} on {} border", self.name, i, border);
+        let needed = self.tiling.tile_count();
+        let pieces = self.pieces();
+        if pieces.len() < needed {
+            bail!(ErrorKind::NotEnoughPieces(self.name.clone(), needed, pieces.len()));
+        }
 
-            self.tiles[i].apply_on(board, border)?;
+        // Pieces without a color go with any other.
+        let mut colors: Vec<RobotId> = Vec::new();
+        for color in pieces.iter().filter_map(|piece| piece.color) {
+            if !colors.contains(&color) {
+                colors.push(color);
+            }
+        }
+        let distinct = colors.len() + pieces.iter().filter(|piece| piece.color.is_none()).count();
+        if distinct < needed {
+            bail!(ErrorKind::NotEnoughColors(self.name.clone(), needed, colors.len()));
+        }
+
+        for (i, tile) in self.tiles.iter().enumerate() {
+            if !self.tiling.fits(tile.side_length(), board) {
+                bail!(ErrorKind::TileSizeMismatch(
+                        self.name.clone(), i, self.tiling,
+                        *board, tile.side_length().clone()));
+            }
+
+            if let Some(pos) = tile.find_out_of_tile() {
+                bail!(ErrorKind::OutOfTilePosition(
+                        self.name.clone(), i, pos, tile.side_length().clone()));
+            }
         }
 
         Ok(())
     }
+
+
+    pub fn pieces(&self) -> Vec<Piece> {
+        if self.pieces.is_empty() {
+            (0..self.tiles.len()).map(Piece::single_face).collect()
+        }
+        else {
+            self.pieces.clone()
+        }
+    }
+
+
+    /// Put each piece once at most, on a random face and in a random
+    /// place, returning targets of the board.
+    pub fn build_rand<T, R>(&self, board: &mut T, rng: &mut R) -> Result<Vec<Target>>
+        where T: AsMut<dyn EditableBoard>,
+              R: Rng
+    {
+        if self.tiles.is_empty() {
+            bail!(ErrorKind::EmptyTileSet(self.name.clone()));
+        }
+
+        let dimensions = board.as_mut().dimensions();
+        let placements = self.tiling.placements(
+            &dimensions, &self.side_length, self.mirror, rng);
+        let pieces = Self::choose_pieces(self.pieces(), placements.len(), rng);
+        if pieces.len() < placements.len() {
+            bail!(ErrorKind::NotEnoughPieces(self.name.clone(), placements.len(), pieces.len()));
+        }
+
+        let mut targets = Vec::new();
+        for (piece, placement) in pieces.iter().zip(placements.iter()) {
+            let i = *piece.faces.choose(rng)
+                .expect("pieces have at least a face");
+
+            self.tiles[i].apply_on(board, placement)?;
+            targets.extend(self.tiles[i].situate_targets(placement));
+        }
+
+        Ok(targets)
+    }
+
+
+    // Pick `count` pieces at most, all of different colors.
+    fn choose_pieces<R>(mut pieces: Vec<Piece>, count: usize, rng: &mut R) -> Vec<Piece>
+        where R: Rng
+    {
+        pieces.shuffle(rng);
+
+        let mut chosen: Vec<Piece> = Vec::new();
+        for piece in pieces.into_iter() {
+            let color_used = piece.color.is_some_and(|color| {
+                chosen.iter().any(|other| other.color == Some(color))
+            });
+            if !color_used {
+                chosen.push(piece);
+            }
+        }
+
+        chosen.truncate(count);
+        chosen
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::positionning::Dimensions;
+
+    use super::super::error::ErrorKind;
+    use super::TileSet;
+
+
+    // Four empty 2x2 tiles, on pieces of `colors`, `null` for no color.
+    fn tile_set(colors: &[&str]) -> TileSet {
+        let pieces: Vec<String> = colors.iter().enumerate()
+            .map(|(i, color)| format!(r#"{{ "color": {}, "faces": [{}] }}"#, color, i))
+            .collect();
+        let mut tile_set: TileSet = serde_json::from_str(&format!(
+                r#"{{ "side_length": 2, "name": "test", "sets": {}, "pieces": [{}] }}"#,
+                serde_json::to_string(&vec![".  .  .  .  "; 4]).expect("tiles serialize"),
+                pieces.join(", ")))
+            .expect("tile set is valid");
+        tile_set.parse().expect("tiles parse");
+        tile_set
+    }
+
+
+    fn board() -> Dimensions {
+        Dimensions { width: 4, height: 4 }
+    }
+
+
+    #[test]
+    fn pieces_of_distinct_colors_are_valid() {
+        let tile_set = tile_set(&[r#""Red""#, r#""Green""#, r#""Blue""#, "null"]);
+        assert!(tile_set.validate(&board()).is_ok());
+    }
+
+
+    #[test]
+    fn pieces_sharing_colors_are_rejected() {
+        let tile_set = tile_set(&[r#""Red""#, r#""Red""#, r#""Blue""#, "null"]);
+        match tile_set.validate(&board()).map_err(|error| error.0) {
+            Err(ErrorKind::NotEnoughColors(_, needed, available)) => {
+                assert_eq!((needed, available), (4, 2));
+            },
+            other => panic!("expected too few colors, got {:?}", other),
+        }
+    }
 }
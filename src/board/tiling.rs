@@ -0,0 +1,334 @@
+use std::fmt;
+
+use rand::Rng;
+use rand::seq::SliceRandom;
+use serde_derive::Deserialize;
+
+use crate::positionning::{LogicalPos, SideLength, Dimensions, RotateAngle, FlipAxis};
+
+use super::border::Border;
+
+
+/// How the tiles of a set are laid out to cover a board.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Tiling {
+    /// One tile in each corner, covering the whole board.
+    #[default]
+    Quadrants,
+    /// One tile in each corner, free rows and columns being left in the
+    /// middle of the board, like on odd sized ones.
+    QuadrantsWithGap,
+    /// A single tile covering the whole board.
+    WholeBoard,
+    /// Tiles side by side, `columns` x `rows` of them covering the board.
+    Grid { columns: usize, rows: usize },
+}
+
+
+/// Where a tile goes on a board, and how it is turned.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Placement {
+    /// Top-left corner of the tile on the board, once turned.
+    pub origin: LogicalPos,
+    /// Quarter turns, clockwise.
+    pub quarter_turns: usize,
+    /// Mirroring of the tile, before it is turned.
+    pub flip: FlipAxis,
+}
+
+
+const FLIPS: [FlipAxis; 4] = [
+    FlipAxis::NoFlip,
+    FlipAxis::FlipHorizontal,
+    FlipAxis::FlipVertical,
+    FlipAxis::FlipBoth,
+];
+
+
+impl Tiling {
+    /// Number of tiles on a board.
+    pub fn tile_count(&self) -> usize {
+        match self {
+            Tiling::Quadrants | Tiling::QuadrantsWithGap => Border::all().len(),
+            Tiling::WholeBoard => 1,
+            Tiling::Grid { columns, rows } => columns * rows,
+        }
+    }
+
+    pub fn fits(&self, tile_side: &SideLength, board: &Dimensions) -> bool {
+        let side = tile_side.0;
+
+        match *self {
+            Tiling::Quadrants => side * 2 == board.width && side * 2 == board.height,
+            Tiling::QuadrantsWithGap => {
+                side > 0 && side * 2 <= board.width && side * 2 <= board.height
+            },
+            Tiling::WholeBoard => side == board.width && side == board.height,
+            Tiling::Grid { columns, rows } => {
+                side * columns == board.width && side * rows == board.height
+            },
+        }
+    }
+
+    // Side length tiles should have, in words.
+    pub fn expected_side(&self, board: &Dimensions) -> String {
+        let exact = |count: usize, length: usize| {
+            if count > 0 && length.is_multiple_of(count) { format!("{}", length / count) }
+            else { "none".to_string() }
+        };
+
+        match *self {
+            Tiling::Quadrants | Tiling::WholeBoard if board.width != board.height => {
+                "none, the board not being a square".to_string()
+            },
+            Tiling::Quadrants => exact(2, board.width),
+            Tiling::QuadrantsWithGap => {
+                format!("at most {}", board.width.min(board.height) / 2)
+            },
+            Tiling::WholeBoard => exact(1, board.width),
+            Tiling::Grid { columns, rows } => {
+                let (width, height) = (exact(columns, board.width), exact(rows, board.height));
+                if width == height { width }
+                else { format!("{} to fill columns and {} to fill rows", width, height) }
+            },
+        }
+    }
+
+    /// Random places for `tile_count` tiles of `tile_side` on a board,
+    /// some of them being mirrored if `mirror` is set.
+    pub fn placements<R>(
+            &self,
+            board: &Dimensions,
+            tile_side: &SideLength,
+            mirror: bool,
+            rng: &mut R) -> Vec<Placement>
+        where R: Rng
+    {
+        let flip = |rng: &mut R| {
+            if mirror { *FLIPS.choose(rng).expect("flips are known") }
+            else { FlipAxis::NoFlip }
+        };
+
+        match *self {
+            Tiling::Quadrants | Tiling::QuadrantsWithGap => {
+                // Where a quadrant goes tells how it is turned.
+                let mut borders = Border::all().to_vec();
+                borders.shuffle(rng);
+                borders.iter()
+                    .map(|border| {
+                        let placement = Placement::at_corner(border, board, tile_side);
+                        if mirror && rng.gen() { placement.transposed() }
+                        else { placement }
+                    })
+                    .collect()
+            },
+            Tiling::WholeBoard => {
+                vec![Placement {
+                    origin: LogicalPos { x: 0, y: 0 },
+                    quarter_turns: rng.gen_range(0, 4),
+                    flip: flip(rng),
+                }]
+            },
+            Tiling::Grid { columns, rows } => {
+                (0..rows)
+                    .flat_map(|row| (0..columns).map(move |column| (column, row)))
+                    .map(|(column, row)| Placement {
+                        origin: LogicalPos { x: column * tile_side.0, y: row * tile_side.0 },
+                        quarter_turns: rng.gen_range(0, 4),
+                        flip: flip(rng),
+                    })
+                    .collect()
+            },
+        }
+    }
+}
+
+
+impl fmt::Display for Tiling {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Tiling::Quadrants => write!(f, "quadrants"),
+            Tiling::QuadrantsWithGap => write!(f, "quadrants-with-gap"),
+            Tiling::WholeBoard => write!(f, "whole-board"),
+            Tiling::Grid { columns, rows } => write!(f, "{}x{} grid", columns, rows),
+        }
+    }
+}
+
+
+impl Placement {
+    // Quadrant on a corner of the board, its center corner (bottom-right when
+    // not turned) toward the center of the board.
+    pub fn at_corner(border: &Border, board: &Dimensions, tile_side: &SideLength) -> Placement {
+        let right = board.width - tile_side.0;
+        let bottom = board.height - tile_side.0;
+
+        let (x, y, quarter_turns) = match border {
+            Border::TopLeft => (0, 0, 0),
+            Border::TopRight => (right, 0, 1),
+            Border::BottomLeft => (right, bottom, 2),
+            Border::BottomRight => (0, bottom, 3),
+        };
+
+        Placement { origin: LogicalPos { x, y }, quarter_turns, flip: FlipAxis::NoFlip }
+    }
+
+    // Same place, the tile being mirrored along its diagonal so that its
+    // center corner stays toward the center of the board.
+    pub fn transposed(self) -> Placement {
+        Placement {
+            quarter_turns: (self.quarter_turns + 3) % 4,
+            flip: FlipAxis::FlipHorizontal,
+            ..self
+        }
+    }
+
+    pub fn angle(&self) -> RotateAngle {
+        match self.quarter_turns % 4 {
+            0 => RotateAngle::NoTurn,
+            1 => RotateAngle::TurnRight,
+            2 => RotateAngle::HalfTurn,
+            _ => RotateAngle::TurnLeft,
+        }
+    }
+
+    // Position on the board of `pos` within a tile of `tile_side`.
+    pub fn situate(&self, pos: &LogicalPos, tile_side: &SideLength) -> LogicalPos {
+        let last = tile_side.0 - 1;
+        let pos = match self.flip {
+            FlipAxis::NoFlip => *pos,
+            FlipAxis::FlipHorizontal => LogicalPos { x: last - pos.x, y: pos.y },
+            FlipAxis::FlipVertical => LogicalPos { x: pos.x, y: last - pos.y },
+            FlipAxis::FlipBoth => LogicalPos { x: last - pos.x, y: last - pos.y },
+        };
+        let (x, y) = match self.quarter_turns % 4 {
+            0 => (pos.x, pos.y),
+            1 => (last - pos.y, pos.x),
+            2 => (last - pos.x, last - pos.y),
+            _ => (pos.y, last - pos.x),
+        };
+
+        LogicalPos { x: self.origin.x + x, y: self.origin.y + y }
+    }
+}
+
+
+impl fmt::Display for Placement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {}) turned {} time(s) clockwise",
+               self.origin.x, self.origin.y, self.quarter_turns)?;
+        match self.flip {
+            FlipAxis::NoFlip => Ok(()),
+            flip => write!(f, ", mirrored ({:?})", flip),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::positionning::{Dimensions, FlipAxis, LogicalPos, SideLength};
+
+    use super::super::border::Border;
+    use super::{Placement, FLIPS};
+
+
+    fn pos(x: usize, y: usize) -> LogicalPos {
+        LogicalPos { x, y }
+    }
+
+
+    #[test]
+    fn situate_turns_clockwise() {
+        let side = SideLength(3);
+        let expected = [pos(1, 0), pos(2, 1), pos(1, 2), pos(0, 1)];
+
+        for (quarter_turns, expected) in expected.iter().enumerate() {
+            let placement = Placement { origin: pos(0, 0), quarter_turns, flip: FlipAxis::NoFlip };
+            // Middle of the top row.
+            assert_eq!(placement.situate(&pos(1, 0), &side), *expected,
+                       "{} quarter turn(s)", quarter_turns);
+        }
+    }
+
+
+    #[test]
+    fn situate_flips_before_turning() {
+        let side = SideLength(3);
+        let cell = pos(0, 0);
+        let expected = [
+            (FlipAxis::NoFlip, pos(0, 0), pos(2, 0)),
+            (FlipAxis::FlipHorizontal, pos(2, 0), pos(2, 2)),
+            (FlipAxis::FlipVertical, pos(0, 2), pos(0, 0)),
+            (FlipAxis::FlipBoth, pos(2, 2), pos(0, 2)),
+        ];
+
+        for &(flip, not_turned, turned) in expected.iter() {
+            let placement = Placement { origin: pos(0, 0), quarter_turns: 0, flip };
+            assert_eq!(placement.situate(&cell, &side), not_turned, "{:?}", flip);
+            let placement = Placement { quarter_turns: 1, ..placement };
+            assert_eq!(placement.situate(&cell, &side), turned, "{:?} turned", flip);
+        }
+    }
+
+
+    #[test]
+    fn situate_offsets_tiles_of_a_grid() {
+        // Second tile of a 2x1 grid on a 6x3 board.
+        let side = SideLength(3);
+        for &flip in FLIPS.iter() {
+            for quarter_turns in 0..4 {
+                let placement = Placement { origin: pos(3, 0), quarter_turns, flip };
+                for y in 0..3 {
+                    for x in 0..3 {
+                        let situated = placement.situate(&pos(x, y), &side);
+                        assert!((3..6).contains(&situated.x) && situated.y < 3,
+                                "{:?} out of its tile with {}", situated, placement);
+                    }
+                }
+            }
+        }
+    }
+
+
+    // Tiles of `side` in the corners of `board`, with and without being
+    // transposed: where their outer and center corners end up.
+    fn check_corners(board: Dimensions, side: SideLength) {
+        let last = side.0 - 1;
+        let (right, bottom) = (board.width - 1, board.height - 1);
+        let expected = [
+            (Border::TopLeft, pos(0, 0), pos(last, last)),
+            (Border::TopRight, pos(right, 0), pos(right - last, last)),
+            (Border::BottomLeft, pos(right, bottom), pos(right - last, bottom - last)),
+            (Border::BottomRight, pos(0, bottom), pos(last, bottom - last)),
+        ];
+
+        for &(border, outer, center) in expected.iter() {
+            let placement = Placement::at_corner(&border, &board, &side);
+            assert_eq!(placement.situate(&pos(0, 0), &side), outer, "{}", border);
+            assert_eq!(placement.situate(&pos(last, last), &side), center, "{}", border);
+
+            let transposed = placement.clone().transposed();
+            assert_eq!(transposed.situate(&pos(0, 0), &side), outer, "{} transposed", border);
+            assert_eq!(transposed.situate(&pos(last, last), &side), center,
+                       "{} transposed", border);
+            // Mirrored along the diagonal: the first row becomes the first
+            // column.
+            assert_eq!(transposed.situate(&pos(1, 0), &side),
+                       placement.situate(&pos(0, 1), &side), "{} transposed", border);
+        }
+    }
+
+
+    #[test]
+    fn quadrants_keep_their_center_corner_inward() {
+        check_corners(Dimensions { width: 16, height: 16 }, SideLength(8));
+    }
+
+
+    #[test]
+    fn quadrants_with_gap_fit_rectangular_boards() {
+        check_corners(Dimensions { width: 10, height: 8 }, SideLength(3));
+    }
+}
@@ -0,0 +1,112 @@
+use crate::net::Role;
+use crate::bench::{BenchOptions, ReportFormat};
+
+use super::error::*;
+
+
+const USAGE: &str = "\
+usage: rrr                                 play locally
+       rrr --terminal                      play locally, in the terminal
+       rrr --serve <addr>                  host a game, e.g. 0.0.0.0:7878
+       rrr --connect <addr> [--name <n>]   join a hosted game
+       rrr --watch <addr> [--name <n>]     watch a hosted game
+       rrr --engine                        answer the engine protocol on stdin
+       rrr validate-tiles [<config>]       check tile sets of a config file
+       rrr bench [--rounds <n>] [--seed <s>] [--target-moves <n>] [--json]
+                                           compare solvers on seeded rounds";
+
+
+#[derive(Debug)]
+pub enum Mode {
+    Local,
+    Terminal,
+    Server { addr: String },
+    Client { addr: String, name: String, role: Role },
+    /// Built-in solver, as an engine for another game.
+    Engine,
+    ValidateTiles { config_path: Option<String> },
+    Bench(BenchOptions),
+}
+
+
+pub fn parse_args<I>(args: I) -> Result<Mode>
+    where I: IntoIterator<Item = String>
+{
+    let mut args = args.into_iter();
+    let mut mode = Mode::Local;
+    let mut name = None;
+    let mut bench = BenchOptions::default();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--terminal" => {
+                mode = Mode::Terminal;
+            },
+            "--serve" => {
+                let addr = value_of(&arg, args.next())?;
+                mode = Mode::Server { addr };
+            },
+            "--connect" => {
+                let addr = value_of(&arg, args.next())?;
+                mode = Mode::Client { addr, name: String::new(), role: Role::Player };
+            },
+            "--watch" => {
+                let addr = value_of(&arg, args.next())?;
+                mode = Mode::Client { addr, name: String::new(), role: Role::Spectator };
+            },
+            "--engine" => {
+                mode = Mode::Engine;
+            },
+            "--name" => {
+                name = Some(value_of(&arg, args.next())?);
+            },
+            "bench" => {
+                mode = Mode::Bench(BenchOptions::default());
+            },
+            "--rounds" => {
+                bench.rounds = number_of(&arg, args.next())? as usize;
+            },
+            "--seed" => {
+                bench.first_seed = number_of(&arg, args.next())?;
+            },
+            "--target-moves" => {
+                bench.max_target_moves = number_of(&arg, args.next())? as usize;
+            },
+            "--json" => {
+                bench.format = ReportFormat::Json;
+            },
+            "validate-tiles" => {
+                mode = Mode::ValidateTiles { config_path: args.next() };
+            },
+            "--help" | "-h" => {
+                bail!(ErrorKind::InvalidArguments(USAGE.to_string()));
+            },
+            unknown => {
+                bail!(ErrorKind::InvalidArguments(
+                        format!("unknown argument '{}'\n{}", unknown, USAGE)));
+            },
+        }
+    }
+
+    if let Mode::Client { name: ref mut client_name, .. } = mode {
+        *client_name = name.unwrap_or_else(|| "anonymous".to_string());
+    }
+    if let Mode::Bench(ref mut options) = mode {
+        *options = bench;
+    }
+
+    Ok(mode)
+}
+
+
+fn value_of(arg: &str, value: Option<String>) -> Result<String> {
+    value.ok_or_else(|| ErrorKind::InvalidArguments(
+            format!("missing value after '{}'\n{}", arg, USAGE)).into())
+}
+
+
+fn number_of(arg: &str, value: Option<String>) -> Result<u64> {
+    let value = value_of(arg, value)?;
+    value.parse().map_err(|_| ErrorKind::InvalidArguments(
+            format!("'{}' is not a number, after '{}'\n{}", value, arg, USAGE)).into())
+}
@@ -1,12 +1,17 @@
 use std::path::{Path, PathBuf, Component};
-use std::fs::File;
+use std::fs::{self, File};
+use std::time::{Duration, SystemTime};
 use std::io::Read;
-use std::collections::VecDeque;
+use std::collections::{VecDeque, HashMap};
 
 use serde_derive::Deserialize;
 
-use crate::positionning::SideLength;
+use crate::positionning::{SideLength, Dimensions};
 use crate::board;
+use crate::game::{Easing, RobotId};
+use crate::palette::Palette;
+use crate::opponent::Difficulty;
+use crate::engine::BUILTIN_NAME;
 
 use super::error::*;
 
@@ -15,11 +20,37 @@ use super::error::*;
 pub struct AppConfig {
     #[serde(default)]
     pub window: WindowConfig,
+    #[serde(default)]
+    pub animation: AnimationConfig,
     pub assets_path: PathBuf,
-    #[serde(rename = "side_length")]
-    pub board_side_length: SideLength,
-    #[serde(rename = "tiles")]
+    /// Describes sprites found in images, `assets_path` being used as a
+    /// single strip of sprites when missing.
+    #[serde(default)]
+    pub atlas_path: Option<PathBuf>,
+    /// Looks to switch between while playing, the first one being used at
+    /// start.
+    #[serde(default)]
+    pub themes: Vec<ThemeConfig>,
+    #[serde(default)]
+    pub accessibility: AccessibilityConfig,
+    /// Size of a square board, unless `size` is given.
+    #[serde(default, rename = "side_length")]
+    pub board_side_length: Option<SideLength>,
+    #[serde(default, rename = "size")]
+    pub board_size: Option<Dimensions>,
+    #[serde(default)]
+    pub board_source: board::BoardSource,
+    #[serde(default, rename = "tiles")]
     pub tile_sets: Vec<board::TileSet>,
+    /// Rules of generated boards.
+    #[serde(default)]
+    pub generator: board::Generator,
+    /// External solvers, the first one giving hints.
+    #[serde(default)]
+    pub engines: Vec<EngineConfig>,
+    /// Playing alone in the window, against computer players.
+    #[serde(default)]
+    pub solo: SoloConfig,
 }
 
 
@@ -42,17 +73,215 @@ impl Default for WindowConfig {
 }
 
 
+#[derive(Debug, Clone, Deserialize)]
+pub struct AnimationConfig {
+    /// Time for a robot to cross one cell, in seconds.
+    #[serde(default = "defaults::seconds_per_cell")]
+    pub seconds_per_cell: f32,
+    #[serde(default)]
+    pub easing: Easing,
+    /// Time for a robot to squash on what stopped it, in seconds.
+    #[serde(default = "defaults::impact_duration")]
+    pub impact_duration: f32,
+    /// How much a robot shrinks on impact, between 0 and 1.
+    #[serde(default = "defaults::impact_squash")]
+    pub impact_squash: f32,
+    /// Speed up animations as they queue up, so quick players don't wait.
+    #[serde(default = "defaults::accelerate_queue")]
+    pub accelerate_queue: bool,
+    /// Jump to the end of the current animation when a new move is played.
+    #[serde(default = "defaults::interrupt_on_input")]
+    pub interrupt_on_input: bool,
+    /// Delay between robots moving together, in seconds.
+    #[serde(default = "defaults::stagger_delay")]
+    pub stagger_delay: f32,
+    /// Time for robots to appear or disappear, in seconds.
+    #[serde(default = "defaults::fade_duration")]
+    pub fade_duration: f32,
+    /// Time for each quarter of a new board to move in place, or for a board
+    /// not made of quadrants to fade in, in seconds.
+    #[serde(default = "defaults::quadrant_duration")]
+    pub quadrant_duration: f32,
+}
+
+
+impl Default for AnimationConfig {
+    fn default() -> AnimationConfig {
+        AnimationConfig {
+            seconds_per_cell: 0.04,
+            easing: Easing::default(),
+            impact_duration: 0.08,
+            impact_squash: 0.2,
+            accelerate_queue: true,
+            interrupt_on_input: true,
+            stagger_delay: 0.05,
+            fade_duration: 0.2,
+            quadrant_duration: 0.5,
+        }
+    }
+}
+
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ThemeConfig {
+    pub name: String,
+    /// Sprites of the theme, the global atlas (or `assets_path`) being used
+    /// when missing.
+    #[serde(default)]
+    pub atlas_path: Option<PathBuf>,
+    #[serde(default = "defaults::background_color")]
+    pub background_color: [u8; 3],
+    #[serde(default)]
+    pub draw_walls_on_edge: bool,
+    /// Tint of each robot sprite, white (the default) leaving it untouched.
+    #[serde(default)]
+    pub robot_colors: HashMap<RobotId, [u8; 3]>,
+    /// Color of what is drawn over the board, like the target.
+    #[serde(default = "defaults::hud_color")]
+    pub hud_color: [u8; 3],
+}
+
+
+impl Default for ThemeConfig {
+    fn default() -> ThemeConfig {
+        ThemeConfig {
+            name: "classic".to_string(),
+            atlas_path: None,
+            background_color: [220, 10, 10],
+            draw_walls_on_edge: false,
+            robot_colors: HashMap::new(),
+            hud_color: [255, 255, 255],
+        }
+    }
+}
+
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AccessibilityConfig {
+    /// Draw a distinct shape on each robot and on the target.
+    #[serde(default)]
+    pub markings: bool,
+    /// Colors of markings and targets, whatever the theme.
+    #[serde(default)]
+    pub palette: Palette,
+}
+
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct EngineConfig {
+    pub name: String,
+    /// Program to start, talking the engine protocol on its standard input
+    /// and output.
+    pub command: PathBuf,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Time to answer, in seconds.
+    #[serde(default = "defaults::engine_timeout")]
+    pub timeout: f32,
+    #[serde(default = "defaults::engine_max_moves")]
+    pub max_moves: usize,
+}
+
+
+impl EngineConfig {
+    /// The built-in solver, answering the engine protocol from `command`,
+    /// this very program.
+    pub fn builtin(command: PathBuf) -> EngineConfig {
+        EngineConfig {
+            name: BUILTIN_NAME.to_string(),
+            command,
+            args: vec!["--engine".to_string()],
+            timeout: defaults::engine_timeout(),
+            max_moves: defaults::engine_max_moves(),
+        }
+    }
+}
+
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SoloConfig {
+    /// Computer players, rounds having no bidding without any.
+    #[serde(default)]
+    pub opponents: Vec<OpponentConfig>,
+    /// Time to bid after the first bid, in seconds.
+    #[serde(default = "defaults::countdown")]
+    pub countdown: f32,
+}
+
+
+impl Default for SoloConfig {
+    fn default() -> SoloConfig {
+        SoloConfig {
+            opponents: Vec::new(),
+            countdown: 30.0,
+        }
+    }
+}
+
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct OpponentConfig {
+    pub name: String,
+    #[serde(default)]
+    pub difficulty: Difficulty,
+    /// Name of one of `engines` to solve rounds with, instead of the
+    /// built-in solver.
+    #[serde(default)]
+    pub engine: Option<String>,
+}
+
+
+impl AppConfig {
+    pub fn board_dimensions(&self) -> Dimensions {
+        self.board_size
+            .or_else(|| self.board_side_length.as_ref().map(Dimensions::square))
+            .unwrap_or_else(|| Dimensions::square(&SideLength(16)))
+    }
+
+    /// Engine of `engines` named `name`.
+    pub fn engine(&self, name: &str) -> Option<&EngineConfig> {
+        self.engines.iter().find(|engine| engine.name == name)
+    }
+
+    /// Configured themes, or the default one if there is none.
+    pub fn themes(&self) -> Vec<ThemeConfig> {
+        if self.themes.is_empty() { vec![ThemeConfig::default()] }
+        else { self.themes.clone() }
+    }
+}
+
+
 mod defaults {
-    use super::WindowConfig;
+    use super::{WindowConfig, AnimationConfig, ThemeConfig, SoloConfig};
 
     pub fn width() -> usize { WindowConfig::default().width }
     pub fn height() -> usize { WindowConfig::default().height }
+
+    pub fn seconds_per_cell() -> f32 { AnimationConfig::default().seconds_per_cell }
+    pub fn impact_duration() -> f32 { AnimationConfig::default().impact_duration }
+    pub fn impact_squash() -> f32 { AnimationConfig::default().impact_squash }
+    pub fn accelerate_queue() -> bool { AnimationConfig::default().accelerate_queue }
+    pub fn interrupt_on_input() -> bool { AnimationConfig::default().interrupt_on_input }
+    pub fn stagger_delay() -> f32 { AnimationConfig::default().stagger_delay }
+    pub fn fade_duration() -> f32 { AnimationConfig::default().fade_duration }
+    pub fn quadrant_duration() -> f32 { AnimationConfig::default().quadrant_duration }
+
+    pub fn background_color() -> [u8; 3] { ThemeConfig::default().background_color }
+    pub fn hud_color() -> [u8; 3] { ThemeConfig::default().hud_color }
+
+    pub fn engine_timeout() -> f32 { 5.0 }
+    pub fn engine_max_moves() -> usize { 10 }
+
+    pub fn countdown() -> f32 { SoloConfig::default().countdown }
 }
 
 
+pub const DEFAULT_PATH: &str = "./config.json5";
+
+
 // loaders
 pub fn load_default() -> Result<AppConfig> {
-    load(Path::new("./config.json5"))
+    load(Path::new(DEFAULT_PATH))
 }
 
 
@@ -71,19 +300,85 @@ pub fn load(path: &Path) -> Result<AppConfig> {
         .and_then(|mut option: AppConfig| {
             let assets_path = std::mem::replace(&mut option.assets_path, PathBuf::new());
             option.assets_path = path_solver.resolve(assets_path);
+            option.atlas_path = option.atlas_path.take()
+                .map(|atlas_path| path_solver.resolve(atlas_path));
+            for theme in option.themes.iter_mut() {
+                theme.atlas_path = theme.atlas_path.take()
+                    .map(|atlas_path| path_solver.resolve(atlas_path));
+            }
             Ok(option)
         })?;
 
 
+    // Engines are timed out with a `Duration`, which cannot be negative.
+    for engine in config.engines.iter() {
+        if engine.timeout <= 0.0 || Duration::try_from_secs_f32(engine.timeout).is_err() {
+            bail!(ErrorKind::InvalidConfig(format!(
+                        "timeout of engine '{}' is not a positive number of seconds",
+                        engine.name)));
+        }
+    }
+
+    for opponent in config.solo.opponents.iter() {
+        if let Some(ref engine) = opponent.engine {
+            if config.engine(engine).is_none() {
+                bail!(ErrorKind::InvalidConfig(format!(
+                            "engine '{}' of opponent '{}' is not in engines",
+                            engine, opponent.name)));
+            }
+        }
+    }
+
     // manually load tiles
+    if config.tile_sets.is_empty() && config.board_source == board::BoardSource::Tiles {
+        let error: board::error::Error = board::error::ErrorKind::NoTileSet.into();
+        return Err(error.into());
+    }
+    let board_dimensions = config.board_dimensions();
     for tile_set in config.tile_sets.iter_mut() {
         tile_set.parse()?;
+        tile_set.validate(&board_dimensions)?;
     }
 
     Ok(config)
 }
 
 
+/// Notice changes of a config file, by polling its modification time.
+pub struct ConfigWatcher {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+}
+
+
+impl ConfigWatcher {
+    pub fn new<P>(path: P) -> ConfigWatcher where P: Into<PathBuf> {
+        let path = path.into();
+        let modified = Self::modified_time(&path);
+        ConfigWatcher { path, modified }
+    }
+
+
+    /// Load the file again if it changed since the last call.
+    pub fn poll(&mut self) -> Option<Result<AppConfig>> {
+        let modified = Self::modified_time(&self.path);
+        if modified == self.modified {
+            return None;
+        }
+
+        self.modified = modified;
+        Some(load(&self.path))
+    }
+
+
+    fn modified_time(path: &Path) -> Option<SystemTime> {
+        fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+    }
+}
+
+
 struct PathSolver(PathBuf);
 
 
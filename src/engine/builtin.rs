@@ -0,0 +1,205 @@
+use std::io::{BufRead, Write};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::thread;
+
+use crate::game::GameState;
+use crate::solver::{Solver, SolverLimits};
+
+use super::notation::{self, Command, Reply, PROTOCOL_VERSION};
+use super::error::*;
+
+
+pub const BUILTIN_NAME: &str = "rrr-solver";
+
+
+/// Answer the engine protocol with the built-in solver, until `quit` or the
+/// end of `input`.
+pub fn run_builtin<R, W>(input: R, mut output: W) -> Result<()>
+    where R: BufRead + Send + 'static,
+          W: Write
+{
+    let mut state = GameState::new();
+
+    // Lines are read on another thread, for `stop` to end a search while it
+    // runs. Each `go` clears it first, a late `stop` cannot end the next one.
+    let stop = Arc::new(AtomicBool::new(false));
+    let (sender, lines) = mpsc::channel();
+    let reader_stop = Arc::clone(&stop);
+    thread::spawn(move || {
+        for line in input.lines() {
+            if let Ok(ref line) = line {
+                match line.split_whitespace().next() {
+                    Some("go") => reader_stop.store(false, Ordering::Relaxed),
+                    Some("stop") => reader_stop.store(true, Ordering::Relaxed),
+                    _ => {},
+                }
+            }
+            if sender.send(line).is_err() {
+                break;
+            }
+        }
+    });
+
+    for line in lines {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let command = match Command::parse(&line) {
+            Ok(command) => command,
+            Err(error) => {
+                reply(&mut output, &Reply::Info(error.to_string()))?;
+                continue;
+            },
+        };
+
+        // Like unknown lines, lines out of the board are told about and
+        // skipped, the game may send a valid position next.
+        match notation::apply_position(&mut state, &command) {
+            Ok(true) => continue,
+            Ok(false) => {},
+            Err(error) => {
+                reply(&mut output, &Reply::Info(error.to_string()))?;
+                continue;
+            },
+        }
+
+        match command {
+            Command::Hello { version } if version != PROTOCOL_VERSION => {
+                let error = format!("unsupported protocol version {}, expected {}",
+                                    version, PROTOCOL_VERSION);
+                reply(&mut output, &Reply::Info(error))?;
+                break;
+            },
+            Command::Hello { .. } => {
+                reply(&mut output, &Reply::Engine { name: BUILTIN_NAME.to_string() })?;
+                reply(&mut output, &Reply::Ready)?;
+            },
+            Command::Go { max_moves, time } => {
+                let limits = SolverLimits {
+                    max_moves,
+                    max_time: Some(time),
+                    stop: Some(Arc::clone(&stop)),
+                    ..SolverLimits::default()
+                };
+                let solution = Solver::new(state.board.as_ref(), limits).solve(&state);
+
+                match solution {
+                    Some(solution) => {
+                        reply(&mut output, &Reply::Info(format!("nodes {}", solution.explored)))?;
+                        reply(&mut output, &Reply::Bid(solution.steps.len()))?;
+                        reply(&mut output, &Reply::Solution(solution.steps))?;
+                    },
+                    None => reply(&mut output, &Reply::NoSolution)?,
+                }
+            },
+            Command::Quit => break,
+            // Read while searching, a `stop` after the answer has nothing
+            // left to end.
+            _ => {},
+        }
+    }
+
+    Ok(())
+}
+
+
+fn reply<W>(output: &mut W, reply: &Reply) -> Result<()> where W: Write {
+    writeln!(output, "{}", reply)?;
+    output.flush()?;
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::{run_builtin, BUILTIN_NAME};
+
+
+    // Replies of the built-in engine to `input`, line by line.
+    fn run(input: &str) -> Vec<String> {
+        let mut output = Vec::new();
+        run_builtin(Cursor::new(input.to_string()), &mut output).expect("engine runs");
+        String::from_utf8(output).expect("replies are text")
+            .lines()
+            .map(str::to_string)
+            .collect()
+    }
+
+
+    // 4x4 board, a robot in each corner, and a wall under (2, 2), the red
+    // robot going right then down to reach its target.
+    const POSITION: &str = "\
+        board 4 4\n\
+        wall 2 2 down\n\
+        robot red 0 0\n\
+        robot green 3 0\n\
+        robot blue 0 3\n\
+        robot yellow 3 3\n\
+        target red 2 2\n";
+
+
+    #[test]
+    fn handshake_names_the_engine() {
+        let replies = run("rrr 1\nquit\nrrr 1\n");
+        assert_eq!(replies, vec![format!("engine {}", BUILTIN_NAME), "ready".to_string()]);
+    }
+
+
+    #[test]
+    fn other_protocol_versions_end_the_session() {
+        let replies = run("rrr 2\nrrr 1\n");
+        assert_eq!(replies.len(), 1);
+        assert!(replies[0].starts_with("info "));
+    }
+
+
+    #[test]
+    fn go_bids_then_answers_the_solution() {
+        let input = format!("rrr 1\n{}go 10 10000\nquit\n", POSITION);
+        let replies = run(&input);
+
+        assert_eq!(replies[..2], ["engine rrr-solver", "ready"]);
+        assert!(replies[2].starts_with("info nodes "));
+        assert_eq!(replies[3..], ["bid 2", "solution red right red down"]);
+    }
+
+
+    #[test]
+    fn stop_before_go_does_not_end_the_search() {
+        let input = format!("{}stop\ngo 10 10000\n", POSITION);
+        let replies = run(&input);
+
+        assert_eq!(replies.last().map(String::as_str), Some("solution red right red down"));
+    }
+
+
+    #[test]
+    fn unsolvable_positions_have_no_solution() {
+        let input = format!("{}go 1 10000\n", POSITION);
+        assert_eq!(run(&input), vec!["nosolution"]);
+    }
+
+
+    #[test]
+    fn lines_out_of_the_board_are_told_and_skipped() {
+        let input = format!("{}robot red 4 0\ngo 10 10000\n", POSITION);
+        let replies = run(&input);
+
+        assert!(replies[0].starts_with("info "));
+        assert_eq!(replies.last().map(String::as_str), Some("solution red right red down"));
+    }
+
+
+    #[test]
+    fn unknown_lines_are_told_and_skipped() {
+        let replies = run("hello\nrrr 1\n");
+        assert!(replies[0].starts_with("info "));
+        assert_eq!(replies[1..], [format!("engine {}", BUILTIN_NAME), "ready".to_string()]);
+    }
+}
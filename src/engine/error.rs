@@ -0,0 +1,43 @@
+use error_chain::error_chain;
+pub use error_chain::bail; // Re-export
+
+use crate::game;
+use crate::board;
+
+
+error_chain! {
+    types {
+        Error, ErrorKind, ResultExt, Result;
+    }
+
+    foreign_links {
+        Io(std::io::Error);
+    }
+
+    links {
+        GameError(game::error::Error, game::error::ErrorKind);
+        BoardError(board::error::Error, board::error::ErrorKind);
+    }
+
+    errors {
+        InvalidLine(line: String) {
+            description("invalid engine protocol line"),
+            display("invalid engine protocol line: '{}'", line),
+        }
+
+        Timeout(engine: String) {
+            description("engine did not answer in time"),
+            display("engine '{}' did not answer in time", engine),
+        }
+
+        EngineExited(engine: String) {
+            description("engine exited"),
+            display("engine '{}' exited", engine),
+        }
+
+        SearchAborted {
+            description("search stopped before its end"),
+            display("search stopped before its end"),
+        }
+    }
+}
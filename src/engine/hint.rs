@@ -0,0 +1,136 @@
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::thread;
+
+use crate::config::{AppConfig, EngineConfig};
+use crate::game::GameState;
+use crate::solver::{Solver, SolverLimits, Step};
+
+use super::notation::{self, Command, robot_name, way_name};
+use super::process::{self, Engine};
+use super::builtin::BUILTIN_NAME;
+use super::error::*;
+
+
+/// Moves an engine would play from a position.
+#[derive(Debug, Clone)]
+pub struct Hint {
+    pub engine: String,
+    pub steps: Vec<Step>,
+}
+
+
+/// Hint being searched for on another thread, so that the game keeps
+/// running meanwhile.
+pub struct PendingHint {
+    result: Receiver<Result<Option<Hint>>>,
+}
+
+
+// Position to solve, described like to an engine, and where to send its hint.
+type Request = (Vec<Command>, Sender<Result<Option<Hint>>>);
+
+
+/// Gives hints from the first engine of a config, or the built-in solver when
+/// there is none. The engine is started with the first hint, then kept
+/// running on its own thread for the next ones.
+pub struct Hinter {
+    engine_config: Option<EngineConfig>,
+    requests: Sender<Request>,
+}
+
+
+impl Hinter {
+    pub fn new(config: &AppConfig) -> Hinter {
+        let engine_config = config.engines.first().cloned();
+        let thread_config = engine_config.clone();
+        let (requests, received) = mpsc::channel::<Request>();
+
+        // Ends once the hinter is dropped, with the engine.
+        thread::spawn(move || {
+            let mut engine = None;
+            for (commands, sender) in received {
+                // Boards cannot be sent to a thread, the position is
+                // described to it like to an engine.
+                let mut state = GameState::new();
+                let hint = commands.iter()
+                    .try_for_each(|command| notation::apply_position(&mut state, command).map(|_| ()))
+                    .and_then(|_| hint_with(thread_config.as_ref(), &mut engine, &state));
+                let _ = sender.send(hint);
+            }
+        });
+
+        Hinter { engine_config, requests }
+    }
+
+    /// Take the engines of `config`, the running one being kept unless
+    /// another one comes first.
+    pub fn update(&mut self, config: &AppConfig) {
+        if config.engines.first() != self.engine_config.as_ref() {
+            *self = Hinter::new(config);
+        }
+    }
+
+    /// Solve `state` on the thread of the engine, `None` meaning no solution
+    /// was found.
+    pub fn start(&self, state: &GameState) -> Result<PendingHint> {
+        let commands = notation::position_commands(state)?;
+        let (sender, result) = mpsc::channel();
+        // A thread that is gone drops `sender`, the hint is then aborted.
+        let _ = self.requests.send((commands, sender));
+        Ok(PendingHint { result })
+    }
+
+    /// Same as `start`, waiting for the hint.
+    pub fn hint(&self, state: &GameState) -> Result<Option<Hint>> {
+        self.start(state)?.result.recv()
+            .unwrap_or_else(|_| Err(ErrorKind::SearchAborted.into()))
+    }
+}
+
+
+impl PendingHint {
+    /// The hint once the search is over, `None` until then.
+    pub fn poll(&self) -> Option<Result<Option<Hint>>> {
+        match self.result.try_recv() {
+            Ok(hint) => Some(hint),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(Err(ErrorKind::SearchAborted.into())),
+        }
+    }
+}
+
+
+// Solve `state` with `engine`, kept running between hints, or with the
+// built-in solver without `engine_config`.
+fn hint_with(
+        engine_config: Option<&EngineConfig>,
+        engine: &mut Option<Engine>,
+        state: &GameState) -> Result<Option<Hint>> {
+    let engine_config = match engine_config {
+        Some(engine_config) => engine_config,
+        None => {
+            let solution = Solver::new(state.board.as_ref(), SolverLimits::default()).solve(state);
+            return Ok(solution.map(|solution| Hint {
+                engine: BUILTIN_NAME.to_string(),
+                steps: solution.steps,
+            }));
+        },
+    };
+
+    let result = process::search_with(engine, engine_config, state, engine_config.max_moves)?;
+    let name = engine.as_ref().map_or(engine_config.name.as_str(), Engine::name);
+    Ok(result.steps.map(|steps| Hint { engine: name.to_string(), steps }))
+}
+
+
+impl Hint {
+    /// First move to play, and how many it takes to reach the target.
+    pub fn describe(&self) -> String {
+        match self.steps.first() {
+            Some(step) => format!("{}: move {} {}, {} move(s) to go",
+                                  self.engine, robot_name(step.robot), way_name(step.way),
+                                  self.steps.len()),
+            None => format!("{}: target already reached", self.engine),
+        }
+    }
+}
@@ -0,0 +1,14 @@
+//! Line-based protocol to talk to solvers running as separate processes, and
+//! the built-in solver speaking it.
+
+pub mod error;
+
+mod notation;
+mod process;
+mod builtin;
+mod hint;
+
+pub use self::notation::{Command, Reply, PROTOCOL_VERSION, position_commands, apply_position};
+pub use self::process::{Engine, SearchResult, search_with};
+pub use self::builtin::{run_builtin, BUILTIN_NAME};
+pub use self::hint::{Hint, Hinter, PendingHint};
@@ -0,0 +1,432 @@
+use std::fmt;
+use std::time::Duration;
+
+use crate::positionning::{LogicalPos, Dimensions, Way};
+use crate::wall::{Wall, Side};
+use crate::board::{self, BoardSnapshot};
+use crate::game::{GameState, RobotId, Target};
+use crate::solver::Step;
+
+use super::error::*;
+
+
+pub const PROTOCOL_VERSION: u32 = 1;
+
+
+/// Line sent by the game to an engine.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    /// `rrr <version>`, first line sent.
+    Hello { version: u32 },
+    /// `board <width> <height>`, starting a new position without walls nor
+    /// robots.
+    Board(Dimensions),
+    /// `wall <x> <y> <right|down>`, forbidden cells being walled in.
+    Wall(Wall),
+    /// `robot <color> <x> <y>`
+    Robot(RobotId, LogicalPos),
+    /// `target <color> <x> <y>`
+    Target(Target),
+    /// `go <max moves> <milliseconds>`, asking for a solution.
+    Go { max_moves: usize, time: Duration },
+    /// `stop`, to answer right away with what was found so far.
+    Stop,
+    /// `quit`
+    Quit,
+}
+
+
+/// Line sent by an engine to the game.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Reply {
+    /// `engine <name>`, answering `rrr`.
+    Engine { name: String },
+    /// `ready`, once the engine can take positions.
+    Ready,
+    /// `info <anything>`, for humans.
+    Info(String),
+    /// `bid <moves>`, the length of a solution the engine is sure of, that
+    /// may be sent before the solution itself.
+    Bid(usize),
+    /// `solution <color> <way> ...`, moves in order, as far as robots go.
+    Solution(Vec<Step>),
+    /// `nosolution`
+    NoSolution,
+}
+
+
+/// Lines describing the position of `state` to an engine.
+pub fn position_commands(state: &GameState) -> Result<Vec<Command>> {
+    let snapshot = BoardSnapshot::from_board(state.board.as_ref())?;
+
+    let mut commands = vec![Command::Board(snapshot.dimensions)];
+    commands.extend(snapshot.walls.into_iter().map(Command::Wall));
+    commands.extend(state.robots.iter()
+        .filter_map(|robot| robot.pos.map(|pos| Command::Robot(robot.id, pos))));
+    commands.extend(state.target.clone().map(Command::Target));
+
+    Ok(commands)
+}
+
+
+/// Update `state` with a line describing a position, telling whether it was
+/// one. `state` is left as it was when the line does not fit the board.
+pub fn apply_position(state: &mut GameState, command: &Command) -> Result<bool> {
+    let on_board = |state: &GameState, pos: &LogicalPos| -> Result<()> {
+        let dimensions = state.board.dimensions();
+        if !dimensions.contains(pos) {
+            let error: board::error::Error =
+                board::error::ErrorKind::OutOfBoardPosition(*pos, dimensions).into();
+            return Err(error.into());
+        }
+        Ok(())
+    };
+
+    match command {
+        Command::Board(dimensions) => {
+            let mut new_state = GameState::new();
+            new_state.board.reset(dimensions)?;
+            *state = new_state;
+        },
+        Command::Wall(wall) => state.board.put_wall(wall)?,
+        Command::Robot(robot, pos) => {
+            on_board(state, pos)?;
+            state.place_robot(*robot, *pos);
+        },
+        Command::Target(target) => {
+            on_board(state, &target.pos)?;
+            state.target = Some(target.clone());
+        },
+        _ => return Ok(false),
+    }
+
+    Ok(true)
+}
+
+
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Command::Hello { version } => write!(f, "rrr {}", version),
+            Command::Board(dimensions) => {
+                write!(f, "board {} {}", dimensions.width, dimensions.height)
+            },
+            Command::Wall(wall) => {
+                write!(f, "wall {} {} {}", wall.pos.x, wall.pos.y, side_name(wall.side))
+            },
+            Command::Robot(robot, pos) => {
+                write!(f, "robot {} {} {}", robot_name(*robot), pos.x, pos.y)
+            },
+            Command::Target(target) => {
+                write!(f, "target {} {} {}", robot_name(target.robot), target.pos.x, target.pos.y)
+            },
+            Command::Go { max_moves, time } => {
+                write!(f, "go {} {}", max_moves, time.as_millis())
+            },
+            Command::Stop => write!(f, "stop"),
+            Command::Quit => write!(f, "quit"),
+        }
+    }
+}
+
+
+impl fmt::Display for Reply {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Reply::Engine { name } => write!(f, "engine {}", name),
+            Reply::Ready => write!(f, "ready"),
+            Reply::Info(text) => write!(f, "info {}", text),
+            Reply::Bid(moves) => write!(f, "bid {}", moves),
+            Reply::Solution(steps) => {
+                write!(f, "solution")?;
+                for step in steps.iter() {
+                    write!(f, " {} {}", robot_name(step.robot), way_name(step.way))?;
+                }
+                Ok(())
+            },
+            Reply::NoSolution => write!(f, "nosolution"),
+        }
+    }
+}
+
+
+impl Command {
+    pub fn parse(line: &str) -> Result<Command> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let invalid = || Error::from(ErrorKind::InvalidLine(line.to_string()));
+        let number = |i: usize| words.get(i)
+            .and_then(|word| word.parse::<usize>().ok())
+            .ok_or_else(invalid);
+        let pos = |i: usize| -> Result<LogicalPos> {
+            Ok(LogicalPos { x: number(i)?, y: number(i + 1)? })
+        };
+        let robot = |i: usize| words.get(i).and_then(|word| parse_robot(word)).ok_or_else(invalid);
+
+        let command = match (words.first().cloned(), words.len()) {
+            (Some("rrr"), 2) => Command::Hello { version: number(1)? as u32 },
+            (Some("board"), 3) => Command::Board(Dimensions { width: number(1)?, height: number(2)? }),
+            (Some("wall"), 4) => {
+                let side = parse_side(words[3]).ok_or_else(invalid)?;
+                Command::Wall(Wall { pos: pos(1)?, side })
+            },
+            (Some("robot"), 4) => Command::Robot(robot(1)?, pos(2)?),
+            (Some("target"), 4) => Command::Target(Target::new(robot(1)?, pos(2)?)),
+            (Some("go"), 3) => Command::Go {
+                max_moves: number(1)?,
+                time: Duration::from_millis(number(2)? as u64),
+            },
+            (Some("stop"), 1) => Command::Stop,
+            (Some("quit"), 1) => Command::Quit,
+            _ => return Err(invalid()),
+        };
+
+        Ok(command)
+    }
+}
+
+
+impl Reply {
+    pub fn parse(line: &str) -> Result<Reply> {
+        let line = line.trim();
+        let invalid = || Error::from(ErrorKind::InvalidLine(line.to_string()));
+        let (keyword, rest) = match line.find(' ') {
+            Some(i) => (&line[..i], line[i + 1..].trim()),
+            None => (line, ""),
+        };
+
+        let reply = match keyword {
+            "engine" => Reply::Engine { name: rest.to_string() },
+            "ready" => Reply::Ready,
+            "info" => Reply::Info(rest.to_string()),
+            "bid" => Reply::Bid(rest.parse().map_err(|_| invalid())?),
+            "solution" => {
+                let words: Vec<&str> = rest.split_whitespace().collect();
+                if !words.len().is_multiple_of(2) {
+                    return Err(invalid());
+                }
+                let steps = words.chunks(2)
+                    .map(|pair| match (parse_robot(pair[0]), parse_way(pair[1])) {
+                        (Some(robot), Some(way)) => Ok(Step { robot, way }),
+                        _ => Err(invalid()),
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                Reply::Solution(steps)
+            },
+            "nosolution" => Reply::NoSolution,
+            _ => return Err(invalid()),
+        };
+
+        Ok(reply)
+    }
+}
+
+
+pub fn robot_name(robot: RobotId) -> &'static str {
+    match robot {
+        RobotId::Red => "red",
+        RobotId::Green => "green",
+        RobotId::Blue => "blue",
+        RobotId::Yellow => "yellow",
+    }
+}
+
+
+pub fn way_name(way: Way) -> &'static str {
+    match way {
+        Way::Up => "up",
+        Way::Down => "down",
+        Way::Left => "left",
+        Way::Right => "right",
+    }
+}
+
+
+fn side_name(side: Side) -> &'static str {
+    match side {
+        Side::Up => "up",
+        Side::Down => "down",
+        Side::Left => "left",
+        Side::Right => "right",
+    }
+}
+
+
+fn parse_robot(word: &str) -> Option<RobotId> {
+    match word {
+        "red" => Some(RobotId::Red),
+        "green" => Some(RobotId::Green),
+        "blue" => Some(RobotId::Blue),
+        "yellow" => Some(RobotId::Yellow),
+        _ => None,
+    }
+}
+
+
+fn parse_way(word: &str) -> Option<Way> {
+    match word {
+        "up" => Some(Way::Up),
+        "down" => Some(Way::Down),
+        "left" => Some(Way::Left),
+        "right" => Some(Way::Right),
+        _ => None,
+    }
+}
+
+
+// Walls are only told on the right or down side of a cell, others being on
+// the right or down side of its neighbour.
+fn parse_side(word: &str) -> Option<Side> {
+    match word {
+        "right" => Some(Side::Right),
+        "down" => Some(Side::Down),
+        _ => None,
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::positionning::{Dimensions, LogicalPos, Way};
+    use crate::wall::{Side, Wall};
+    use crate::game::{GameState, RobotId, Target};
+    use crate::solver::Step;
+
+    use super::{Command, Reply, apply_position, position_commands};
+
+
+    fn pos(x: usize, y: usize) -> LogicalPos {
+        LogicalPos { x, y }
+    }
+
+
+    fn board(width: usize, height: usize) -> GameState {
+        let mut state = GameState::new();
+        apply_position(&mut state, &Command::Board(Dimensions { width, height }))
+            .expect("board fits");
+        state
+    }
+
+
+    #[test]
+    fn commands_are_parsed_back_from_their_lines() {
+        let commands = vec![
+            Command::Hello { version: 1 },
+            Command::Board(Dimensions { width: 16, height: 12 }),
+            Command::Wall(Wall { pos: pos(3, 4), side: Side::Right }),
+            Command::Wall(Wall { pos: pos(0, 15), side: Side::Down }),
+            Command::Robot(RobotId::Yellow, pos(7, 2)),
+            Command::Target(Target::new(RobotId::Green, pos(1, 9))),
+            Command::Go { max_moves: 8, time: Duration::from_millis(1500) },
+            Command::Stop,
+            Command::Quit,
+        ];
+
+        for command in commands {
+            let line = command.to_string();
+            assert_eq!(Command::parse(&line).expect("line is valid"), command, "{}", line);
+        }
+        assert_eq!(Command::parse("  robot   blue 2 3 ").ok(),
+                   Some(Command::Robot(RobotId::Blue, pos(2, 3))));
+    }
+
+
+    #[test]
+    fn invalid_commands_are_refused() {
+        let lines = [
+            "", "rrr", "rrr one", "board 16", "board -1 16", "robot purple 1 2",
+            "target red 1", "wall 1 2 up", "wall 1 2 left", "wall 1 2 across",
+            "go 10", "stop now", "castle",
+        ];
+
+        for line in lines.iter() {
+            assert!(Command::parse(line).is_err(), "'{}' should be refused", line);
+        }
+    }
+
+
+    #[test]
+    fn replies_are_parsed_back_from_their_lines() {
+        let replies = vec![
+            Reply::Engine { name: "deep solver 2".to_string() },
+            Reply::Ready,
+            Reply::Info("nodes 1234".to_string()),
+            Reply::Bid(5),
+            Reply::Solution(vec![
+                Step { robot: RobotId::Red, way: Way::Up },
+                Step { robot: RobotId::Blue, way: Way::Left },
+            ]),
+            Reply::Solution(Vec::new()),
+            Reply::NoSolution,
+        ];
+
+        for reply in replies {
+            let line = reply.to_string();
+            assert_eq!(Reply::parse(&line).expect("line is valid"), reply, "{}", line);
+        }
+    }
+
+
+    #[test]
+    fn invalid_replies_are_refused() {
+        let lines = ["", "bid", "bid many", "solution red", "solution red sideways", "hello"];
+
+        for line in lines.iter() {
+            assert!(Reply::parse(line).is_err(), "'{}' should be refused", line);
+        }
+    }
+
+
+    #[test]
+    fn positions_are_rebuilt_from_their_commands() {
+        let mut state = board(5, 4);
+        state.board.put_wall(&Wall { pos: pos(1, 1), side: Side::Right }).expect("wall fits");
+        state.board.put_wall(&Wall { pos: pos(2, 3), side: Side::Up }).expect("wall fits");
+        state.place_robot(RobotId::Red, pos(0, 0));
+        state.place_robot(RobotId::Blue, pos(4, 3));
+        state.target = Some(Target::new(RobotId::Red, pos(3, 1)));
+
+        let commands = position_commands(&state).expect("position is described");
+        let mut rebuilt = GameState::new();
+        for command in commands.iter() {
+            assert!(apply_position(&mut rebuilt, command).expect("command fits"));
+        }
+
+        assert_eq!(position_commands(&rebuilt).expect("position is described"), commands);
+        assert_eq!(rebuilt.robot_pos(RobotId::Blue), Some(pos(4, 3)));
+        assert_eq!(rebuilt.robot_pos(RobotId::Green), None);
+        assert_eq!(rebuilt.target, state.target);
+    }
+
+
+    #[test]
+    fn apply_position_ignores_other_commands() {
+        let mut state = board(4, 4);
+        assert!(!apply_position(&mut state, &Command::Stop).expect("stop is no position"));
+        assert!(!apply_position(&mut state, &Command::Hello { version: 1 })
+                .expect("hello is no position"));
+    }
+
+
+    #[test]
+    fn apply_position_refuses_what_is_out_of_the_board() {
+        let mut state = board(4, 3);
+        state.place_robot(RobotId::Red, pos(1, 1));
+
+        let outside = [
+            Command::Wall(Wall { pos: pos(4, 0), side: Side::Right }),
+            Command::Wall(Wall { pos: pos(0, 3), side: Side::Down }),
+            Command::Robot(RobotId::Red, pos(4, 1)),
+            Command::Robot(RobotId::Green, pos(0, 3)),
+            Command::Target(Target::new(RobotId::Red, pos(9, 9))),
+        ];
+
+        for command in outside.iter() {
+            assert!(apply_position(&mut state, command).is_err(), "{} should be refused", command);
+        }
+        assert_eq!(state.robot_pos(RobotId::Red), Some(pos(1, 1)));
+        assert_eq!(state.robot_pos(RobotId::Green), None);
+        assert_eq!(state.target, None);
+    }
+}
@@ -0,0 +1,235 @@
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command as Process, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::config::EngineConfig;
+use crate::game::GameState;
+use crate::solver::Step;
+
+use super::notation::{self, Command, Reply, PROTOCOL_VERSION};
+use super::error::*;
+
+
+// Time left to an engine to answer `stop` before giving up on it.
+const STOP_GRACE: Duration = Duration::from_millis(500);
+
+
+/// What an engine found on a position.
+#[derive(Debug, Clone)]
+pub struct SearchResult {
+    /// Moves the engine announced, before or without a solution.
+    pub bid: Option<usize>,
+    /// `None` when the engine found no solution.
+    pub steps: Option<Vec<Step>>,
+    /// Positions explored, when the engine tells with `info nodes <n>`.
+    pub nodes: Option<usize>,
+    pub elapsed: Duration,
+}
+
+
+/// External solver, talking the engine protocol on its standard input and
+/// output.
+pub struct Engine {
+    config: EngineConfig,
+    name: String,
+    child: Child,
+    input: ChildStdin,
+    replies: Receiver<String>,
+    timeout: Duration,
+}
+
+
+impl Engine {
+    /// Start the engine of `config` and wait for it to be ready.
+    pub fn spawn(config: &EngineConfig) -> Result<Engine> {
+        let (child, input, replies) = Self::start(config)?;
+        let mut engine = Engine {
+            config: config.clone(),
+            name: config.name.clone(),
+            child,
+            input,
+            replies,
+            timeout: Duration::from_secs_f32(config.timeout),
+        };
+        engine.handshake()?;
+        Ok(engine)
+    }
+
+    fn start(config: &EngineConfig) -> Result<(Child, ChildStdin, Receiver<String>)> {
+        let mut child = Process::new(&config.command)
+            .args(&config.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+
+        let input = child.stdin.take().expect("stdin is piped");
+        let output = child.stdout.take().expect("stdout is piped");
+
+        // Reading blocks, lines go through a channel to wait with a timeout.
+        let (sender, replies) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(output).lines() {
+                let sent = line.ok().and_then(|line| sender.send(line).ok());
+                if sent.is_none() {
+                    break;
+                }
+            }
+        });
+
+        Ok((child, input, replies))
+    }
+
+    // Start the engine again, so that what the previous one would still send
+    // is not taken for answers to the next search.
+    fn restart(&mut self) -> Result<()> {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+
+        let (child, input, replies) = Self::start(&self.config)?;
+        self.child = child;
+        self.input = input;
+        self.replies = replies;
+        self.handshake()
+    }
+
+    /// Name announced by the engine.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Ask for a solution of `state` in at most `max_moves` moves, the engine
+    /// being stopped when it takes longer than its timeout.
+    pub fn search(&mut self, state: &GameState, max_moves: usize) -> Result<SearchResult> {
+        // Lines sent after the answer to a previous search.
+        while self.replies.try_recv().is_ok() {}
+
+        for command in notation::position_commands(state)? {
+            self.send(&command)?;
+        }
+        self.send(&Command::Go { max_moves, time: self.timeout })?;
+
+        let started = Instant::now();
+        let mut deadline = started + self.timeout;
+        let mut stopped = false;
+        let mut bid = None;
+        let mut nodes = None;
+
+        loop {
+            let reply = match self.recv_until(deadline)? {
+                Some(reply) => reply,
+                None if stopped => {
+                    let timeout = ErrorKind::Timeout(self.name.clone());
+                    return Err(match self.restart() {
+                        Ok(()) => timeout.into(),
+                        Err(error) => Error::with_chain(error, timeout),
+                    });
+                },
+                None => {
+                    self.send(&Command::Stop)?;
+                    stopped = true;
+                    deadline = Instant::now() + STOP_GRACE;
+                    continue;
+                },
+            };
+
+            match reply {
+                Reply::Info(text) => {
+                    nodes = text.strip_prefix("nodes ")
+                        .and_then(|count| count.trim().parse().ok())
+                        .or(nodes);
+                },
+                Reply::Bid(moves) => bid = Some(moves),
+                Reply::Solution(steps) => {
+                    let bid = bid.or(Some(steps.len()));
+                    let elapsed = started.elapsed();
+                    return Ok(SearchResult { bid, steps: Some(steps), nodes, elapsed });
+                },
+                Reply::NoSolution => {
+                    return Ok(SearchResult { bid, steps: None, nodes, elapsed: started.elapsed() });
+                },
+                _ => {},
+            }
+        }
+    }
+
+    fn handshake(&mut self) -> Result<()> {
+        self.send(&Command::Hello { version: PROTOCOL_VERSION })?;
+        let deadline = Instant::now() + self.timeout;
+
+        loop {
+            match self.recv_until(deadline)? {
+                Some(Reply::Engine { name }) => {
+                    if !name.is_empty() {
+                        self.name = name;
+                    }
+                },
+                Some(Reply::Ready) => return Ok(()),
+                Some(_) => {},
+                None => bail!(ErrorKind::Timeout(self.name.clone())),
+            }
+        }
+    }
+
+    fn send(&mut self, command: &Command) -> Result<()> {
+        let input = &mut self.input;
+        writeln!(input, "{}", command)
+            .and_then(|_| input.flush())
+            .chain_err(|| ErrorKind::EngineExited(self.name.clone()))
+    }
+
+    // Next reply, `None` once `deadline` is passed. Lines that are not part of
+    // the protocol are ignored, engines may print anything while starting.
+    fn recv_until(&mut self, deadline: Instant) -> Result<Option<Reply>> {
+        loop {
+            let now = Instant::now();
+            if now >= deadline {
+                return Ok(None);
+            }
+
+            match self.replies.recv_timeout(deadline - now) {
+                Ok(line) => {
+                    if let Ok(reply) = Reply::parse(&line) {
+                        return Ok(Some(reply));
+                    }
+                },
+                Err(RecvTimeoutError::Timeout) => return Ok(None),
+                Err(RecvTimeoutError::Disconnected) => {
+                    bail!(ErrorKind::EngineExited(self.name.clone()))
+                },
+            }
+        }
+    }
+}
+
+
+/// Search with `engine`, started from `config` when it is not running yet,
+/// for it to be kept between searches. An engine that failed is dropped, and
+/// started again with the next search.
+pub fn search_with(
+        engine: &mut Option<Engine>,
+        config: &EngineConfig,
+        state: &GameState,
+        max_moves: usize) -> Result<SearchResult> {
+    if engine.is_none() {
+        *engine = Some(Engine::spawn(config)?);
+    }
+    let result = engine.as_mut()
+        .expect("engine was started")
+        .search(state, max_moves);
+
+    if result.is_err() {
+        *engine = None;
+    }
+    result
+}
+
+
+impl Drop for Engine {
+    fn drop(&mut self) {
+        let _ = self.send(&Command::Quit);
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
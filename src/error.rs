@@ -1,9 +1,12 @@
 use error_chain::error_chain;
 pub use error_chain::bail; // Re-export
 
+#[cfg(feature = "sdl")]
 use crate::graphics;
 use crate::game;
 use crate::board;
+use crate::net;
+use crate::engine;
 
 
 error_chain! {
@@ -14,11 +17,31 @@ error_chain! {
     foreign_links {
         Io(std::io::Error);
         Json5(json5::Error);
+        Json(serde_json::Error);
     }
 
     links {
-        GraphicsError(graphics::error::Error, graphics::error::ErrorKind);
+        GraphicsError(graphics::error::Error, graphics::error::ErrorKind) #[cfg(feature = "sdl")];
         GameError(game::error::Error, game::error::ErrorKind);
         BoardError(board::error::Error, board::error::ErrorKind);
+        NetError(net::error::Error, net::error::ErrorKind);
+        EngineError(engine::error::Error, engine::error::ErrorKind);
+    }
+
+    errors {
+        InvalidArguments(msg: String) {
+            description("invalid command line arguments"),
+            display("invalid command line arguments: {}", msg),
+        }
+
+        InvalidConfig(msg: String) {
+            description("invalid config"),
+            display("invalid config: {}", msg),
+        }
+
+        TileWarnings(count: usize) {
+            description("tile sets have warnings"),
+            display("{} warning{} in tile sets", count, if *count == 1 { "" } else { "s" }),
+        }
     }
 }
@@ -1,7 +1,12 @@
+use std::f32::consts::PI;
+
 use cgmath::prelude::*;
+use cgmath::Vector2;
 
 use crate::positionning::PhysicalPos;
+use crate::config::AnimationConfig;
 
+use super::easing::Easing;
 use super::robot::RobotId;
 use super::world::World;
 use super::game_state::GameState;
@@ -16,15 +21,21 @@ pub trait Animation {
         world: &mut World,
         elapsed: f32
     ) -> bool;
+    /// Jump to the end, leaving the world as if the animation was played.
+    fn finish(&mut self, state: &GameState, world: &mut World);
 }
 
 
+/// Slide a robot, then squash it against what stopped it.
 pub struct MoveRobotAnimation {
     robot_id: RobotId,
     source_pos: PhysicalPos,
     target_pos: PhysicalPos,
     time: f32,
     duration: f32,
+    easing: Easing,
+    impact_duration: f32,
+    impact_squash: f32,
 }
 
 
@@ -33,13 +44,33 @@ impl MoveRobotAnimation {
             robot_id: RobotId,
             source_pos: PhysicalPos,
             target_pos: PhysicalPos,
-            duration: f32) -> MoveRobotAnimation {
+            config: &AnimationConfig) -> MoveRobotAnimation {
+        let move_duration = config.seconds_per_cell * source_pos.distance(target_pos);
+
         MoveRobotAnimation {
             robot_id,
             source_pos,
             target_pos,
             time: 0f32,
-            duration,
+            duration: move_duration + config.impact_duration,
+            easing: config.easing,
+            impact_duration: config.impact_duration,
+            impact_squash: config.impact_squash,
+        }
+    }
+
+    // Shrink along the move, and grow across it to keep the same area.
+    fn impact_scale(&self, t: f32) -> Vector2<f32> {
+        let squash = self.impact_squash * (PI * t).sin();
+        let along = 1f32 - squash;
+        let across = 1f32 + squash * 0.5f32;
+
+        if (self.target_pos.x - self.source_pos.x).abs()
+            >= (self.target_pos.y - self.source_pos.y).abs() {
+            Vector2::new(along, across)
+        }
+        else {
+            Vector2::new(across, along)
         }
     }
 }
@@ -66,21 +97,80 @@ impl Animation for MoveRobotAnimation {
         let robot = &mut world.robots[robot_index];
 
         self.time += elapsed;
-        if self.time < self.duration {
-            let t = self.time / self.duration;
+        let move_duration = self.duration - self.impact_duration;
+
+        if self.time < move_duration {
+            let t = self.easing.apply(self.time / move_duration);
             robot.pos = Some(
                 self.source_pos.lerp(self.target_pos, t)
             );
-            return true;
+            robot.scale = Vector2::new(1f32, 1f32);
+            true
+        }
+        else if self.time < self.duration {
+            let t = (self.time - move_duration) / self.impact_duration;
+            robot.pos = Some(self.target_pos);
+            robot.scale = self.impact_scale(t);
+            true
         }
         else {
             robot.pos = Some(self.target_pos);
-            return false;
+            robot.scale = Vector2::new(1f32, 1f32);
+            false
+        }
+    }
+
+    fn finish(&mut self, state: &GameState, world: &mut World) {
+        self.time = self.duration;
+
+        if let Some(robot_index) = state.robot_index(self.robot_id) {
+            let robot = &mut world.robots[robot_index];
+            robot.pos = Some(self.target_pos);
+            robot.scale = Vector2::new(1f32, 1f32);
         }
     }
 }
 
 
+/// Does nothing for a while, to offset other animations.
+pub struct DelayAnimation {
+    time: f32,
+    duration: f32,
+}
+
+
+impl DelayAnimation {
+    pub fn new(duration: f32) -> DelayAnimation {
+        DelayAnimation { time: 0f32, duration }
+    }
+}
+
+
+impl Animation for DelayAnimation {
+    fn get_time(&self) -> f32 {
+        self.time
+    }
+
+    fn get_duration(&self) -> f32 {
+        self.duration
+    }
+
+    fn render(
+        &mut self,
+        _state: &GameState,
+        _world: &mut World,
+        elapsed: f32
+    ) -> bool {
+        self.time += elapsed;
+        self.time < self.duration
+    }
+
+    fn finish(&mut self, _state: &GameState, _world: &mut World) {
+        self.time = self.duration;
+    }
+}
+
+
 pub struct AnimationSequence {
     animations: Vec<Box<dyn Animation>>,
     time: f32,
@@ -123,8 +213,9 @@ impl Animation for AnimationSequence {
         while self.current_animation < self.animations.len()
         && self.animations[self.current_animation].get_time() + remaining >= self.animations[self.current_animation].get_duration() {
             let animation = &mut self.animations[self.current_animation];
-            animation.render(state, world, remaining);
-            remaining -= animation.get_duration() - animation.get_time();
+            let left = animation.get_duration() - animation.get_time();
+            animation.finish(state, world);
+            remaining -= left;
             self.current_animation += 1;
         }
 
@@ -136,10 +227,84 @@ impl Animation for AnimationSequence {
             self.time < self.duration
         }
         else {
-            let last_index = self.animations.len() - 1;
-            let animation = &mut self.animations[last_index];
-            animation.render(state, world, elapsed);
             false
         }
     }
+
+    fn finish(&mut self, state: &GameState, world: &mut World) {
+        for animation in self.animations[self.current_animation..].iter_mut() {
+            animation.finish(state, world);
+        }
+
+        self.current_animation = self.animations.len();
+        self.time = self.duration;
+    }
+}
+
+
+/// Play animations all at once, ends with the longest one.
+pub struct ParallelAnimation {
+    animations: Vec<Box<dyn Animation>>,
+    time: f32,
+    duration: f32,
+}
+
+impl ParallelAnimation {
+    pub fn new() -> ParallelAnimation {
+        ParallelAnimation {
+            animations: Vec::new(),
+            time: 0.0f32,
+            duration: 0.0f32,
+        }
+    }
+
+    pub fn add_animation(&mut self, animation: Box<dyn Animation>) {
+        self.duration = self.duration.max(animation.get_duration());
+        self.animations.push(animation);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.animations.is_empty()
+    }
+}
+
+impl Animation for ParallelAnimation {
+    fn get_time(&self) -> f32 {
+        self.time
+    }
+
+    fn get_duration(&self) -> f32 {
+        self.duration
+    }
+
+    fn render(
+        &mut self,
+        state: &GameState,
+        world: &mut World,
+        elapsed: f32
+    ) -> bool {
+        self.time += elapsed;
+
+        for animation in self.animations.iter_mut() {
+            if animation.get_time() >= animation.get_duration() {
+                continue;
+            }
+            else if animation.get_time() + elapsed >= animation.get_duration() {
+                animation.finish(state, world);
+            }
+            else {
+                animation.render(state, world, elapsed);
+            }
+        }
+
+        self.time < self.duration
+    }
+
+    fn finish(&mut self, state: &GameState, world: &mut World) {
+        for animation in self.animations.iter_mut() {
+            animation.finish(state, world);
+        }
+
+        self.time = self.duration;
+    }
 }
@@ -1,11 +1,39 @@
+use serde_derive::{Serialize, Deserialize};
+
 use super::error::Result;
 use super::Game;
+use super::game_state::GameState;
+use super::move_robot_command::MoveRobotCommand;
 
 
 pub trait CommandBase {
     fn redo(&self, game: &mut Game) -> Result<()>;
     fn undo(&self, game: &mut Game) -> Result<()>;
+    fn serialize(&self) -> SerializedCommand;
 }
 
 
 pub trait Command : std::fmt::Debug + CommandBase {}
+
+
+/// Owned form of every command, so they can be sent over the network.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SerializedCommand {
+    MoveRobot(MoveRobotCommand),
+}
+
+
+impl SerializedCommand {
+    pub fn into_command(self) -> Box<dyn Command> {
+        match self {
+            SerializedCommand::MoveRobot(command) => Box::new(command),
+        }
+    }
+
+    // Check whether the command could be executed by a player from `state`.
+    pub fn is_valid_on(&self, state: &GameState) -> bool {
+        match self {
+            SerializedCommand::MoveRobot(command) => command.is_valid_on(state),
+        }
+    }
+}
@@ -0,0 +1,34 @@
+use std::f32::consts::PI;
+
+use serde_derive::Deserialize;
+
+
+/// Shape of the progression of an animation over time.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Easing {
+    Linear,
+    EaseIn,
+    #[default]
+    EaseOut,
+    EaseInOut,
+    /// Overshoot the end and wobble back to it, like bumping into a wall.
+    Elastic,
+}
+
+
+impl Easing {
+    // Map a linear progression in [0, 1] to the eased one. The result starts
+    // at 0 and ends at 1, but may go out of this range in between.
+    pub fn apply(&self, t: f32) -> f32 {
+        let t = t.clamp(0f32, 1f32);
+
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t,
+            Easing::EaseOut => 1f32 - (1f32 - t) * (1f32 - t),
+            Easing::EaseInOut => t * t * (3f32 - 2f32 * t),
+            Easing::Elastic => 1f32 - (2.5f32 * PI * t).cos() * (-6f32 * t).exp(),
+        }
+    }
+}
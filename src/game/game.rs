@@ -1,44 +1,74 @@
-use cgmath::prelude::*;
+use std::rc::Rc;
 
-use crate::positionning::{LogicalPos, Way, physical_from_logical};
+use crate::positionning::{LogicalPos, PhysicalPos, Way, physical_from_logical};
+use crate::config::AnimationConfig;
 
 use super::error::*;
 use super::robot::RobotId;
 use super::game_state::GameState;
 use super::world::World;
-use super::command::Command;
+use super::command::{Command, SerializedCommand};
+use super::history::HistoryTree;
 use super::move_robot_command::MoveRobotCommand;
 use super::animation::{
     Animation,
     MoveRobotAnimation,
+    DelayAnimation,
     AnimationSequence,
+    ParallelAnimation,
 };
+use super::transition::BoardTransitionAnimation;
 
 
 pub struct Game {
     pub state: GameState,
     pub world: World,
-    undo_stack: Vec<Box<dyn Command>>,
-    redo_stack: Vec<Box<dyn Command>>,
+    history: HistoryTree,
     animation: Option<AnimationSequence>,
     animation_speed: f32,
+    animation_config: AnimationConfig,
+    // Whether commands start their own animation.
+    animated: bool,
+    // Robots jump to their place, for frontends drawing no animation.
+    instant: bool,
+    // Demonstrations are played at a constant pace, to look the same for
+    // every viewer.
+    demonstrating: bool,
 }
 
 
 impl Game {
     pub fn new() -> Game {
+        Game::with_animation(AnimationConfig::default())
+    }
+
+    pub fn with_animation(animation_config: AnimationConfig) -> Game {
         Game {
             state: GameState::new(),
             world: World::new(),
-            undo_stack: Vec::new(),
-            redo_stack: Vec::new(),
+            history: HistoryTree::new(),
             animation: None,
             animation_speed: 1.0f32,
+            animation_config,
+            animated: true,
+            instant: false,
+            demonstrating: false,
+        }
+    }
+
+    /// Game where nothing is animated, the world following the state right
+    /// away.
+    pub fn without_animation() -> Game {
+        Game {
+            instant: true,
+            ..Game::new()
         }
     }
 
     pub fn try_move_robot_in_dir(&mut self, robot: RobotId, way: Way)
         -> Result<bool> {
+        self.interrupt_animation();
+
         let source_pos = self.state
             .robot_pos(robot)
             .ok_or(ErrorKind::RobotHasNoPosition)?;
@@ -66,26 +96,58 @@ impl Game {
         self.exec_command(Box::new(command))
     }
 
+    pub fn exec_serialized(&mut self, command: SerializedCommand) -> Result<()> {
+        self.exec_command(command.into_command())
+    }
+
+    /// Play a solution from the position where the round started.
+    pub fn demonstrate(&mut self, commands: Vec<SerializedCommand>) -> Result<()> {
+        self.start_demonstration()?;
+        for command in commands {
+            self.exec_serialized(command)?;
+        }
+
+        Ok(())
+    }
+
+    /// Put robots back where the round started, moves played next being
+    /// animated at a constant pace.
+    pub fn start_demonstration(&mut self) -> Result<()> {
+        self.undo_all()?;
+        self.skip_animation();
+        self.demonstrating = true;
+        Ok(())
+    }
+
+    // Commands leading from the last reset to the current state.
+    pub fn history(&self) -> Vec<SerializedCommand> {
+        self.history.commands()
+    }
+
+    // A move played before from the same position is visited again, instead
+    // of starting a branch of its own.
     fn exec_command(&mut self, command: Box<dyn Command>) -> Result<()> {
-        println!("Exec command: {:?}", command);
         let res = command.redo(self);
-        self.undo_stack.push(command);
-        self.redo_stack.clear();
+        match self.history.find_child(&command.serialize()) {
+            Some(child) => self.history.enter(child),
+            None => self.history.push(Rc::from(command)),
+        }
         res
     }
 
 
     pub fn clear_undo_stack(&mut self) {
-        self.undo_stack.clear();
-        self.redo_stack.clear();
+        self.history.clear();
     }
 
 
     pub fn undo(&mut self) -> Result<bool> {
-        match self.undo_stack.pop() {
+        self.interrupt_animation();
+
+        match self.history.current_command() {
             Some(command) => {
                 command.undo(self)?;
-                self.redo_stack.push(command);
+                self.history.leave();
                 Ok(true)
             }
             None => {
@@ -96,10 +158,14 @@ impl Game {
 
 
     pub fn redo(&mut self) -> Result<bool> {
-        match self.redo_stack.pop() {
-            Some(command) => {
-                command.redo(self)?;
-                self.undo_stack.push(command);
+        self.interrupt_animation();
+
+        match self.history.next() {
+            Some(child) => {
+                if let Some(command) = self.history.command(child) {
+                    command.redo(self)?;
+                }
+                self.history.enter(child);
                 Ok(true)
             }
             None => {
@@ -109,7 +175,189 @@ impl Game {
     }
 
 
+    /// Undo everything, robots going back to their place all at once.
+    pub fn undo_all(&mut self) -> Result<bool> {
+        self.repeat_at_once(|game| game.undo())
+    }
+
+
+    pub fn redo_all(&mut self) -> Result<bool> {
+        self.repeat_at_once(|game| game.redo())
+    }
+
+
+    /// Play another move from the same position as the last one, in the
+    /// order they were first played, `forward` or not.
+    pub fn switch_sibling(&mut self, forward: bool) -> Result<bool> {
+        let siblings = self.history.siblings();
+        let index = match siblings.iter().position(|&node| node == self.history.current()) {
+            Some(index) if siblings.len() > 1 => index,
+            _ => return Ok(false),
+        };
+
+        let count = siblings.len();
+        let sibling = siblings[if forward { (index + 1) % count } else { (index + count - 1) % count }];
+        self.go_to(sibling)
+    }
+
+
+    /// Go to the end of the next line of moves explored, cycling through all
+    /// of them, and tell which one it is out of how many.
+    pub fn switch_variant(&mut self) -> Result<Option<(usize, usize)>> {
+        let variants = self.history.variants();
+        let index = variants.iter()
+            .position(|&node| node == self.history.variant())
+            .unwrap_or(0);
+        if variants.len() < 2 {
+            return Ok(None);
+        }
+
+        let next = (index + 1) % variants.len();
+        self.go_to(variants[next])?;
+        Ok(Some((next + 1, variants.len())))
+    }
+
+
+    // Undo until `node` is ahead, then redo up to it, robots moving straight
+    // to their final position.
+    fn go_to(&mut self, node: usize) -> Result<bool> {
+        self.repeat_at_once(|game| {
+            if game.history.current() == node {
+                return Ok(false);
+            }
+
+            match game.history.child_towards(node) {
+                Some(child) => {
+                    game.history.set_next(child);
+                    game.redo()
+                },
+                None => game.undo(),
+            }
+        })
+    }
+
+
+    // Repeat `step` until it returns false, then move every robot straight to
+    // its final position, in parallel.
+    fn repeat_at_once<F>(&mut self, step: F) -> Result<bool>
+        where F: Fn(&mut Game) -> Result<bool>
+    {
+        self.skip_animation();
+
+        let initial_pos: Vec<_> = self.state.robots.iter()
+            .map(|robot| (robot.id, robot.pos))
+            .collect();
+
+        self.animated = false;
+        let mut changed = false;
+        let result = loop {
+            match step(self) {
+                Ok(true) => { changed = true; },
+                Ok(false) => { break Ok(changed); },
+                Err(error) => { break Err(error); },
+            }
+        };
+        self.animated = true;
+
+        if self.instant {
+            self.world.reset(&self.state);
+            return result;
+        }
+
+        let mut group = ParallelAnimation::new();
+        let mut delay = 0f32;
+
+        for (robot, source_pos) in initial_pos.into_iter() {
+            let target_pos = self.state.robot_pos(robot);
+
+            if let (Some(source_pos), Some(target_pos)) = (source_pos, target_pos) {
+                if source_pos == target_pos {
+                    continue;
+                }
+
+                let mut staggered = AnimationSequence::new();
+                staggered.add_animation(Box::new(DelayAnimation::new(delay)));
+                staggered.add_animation(
+                    Box::new(
+                        MoveRobotAnimation::new(
+                            robot,
+                            physical_from_logical(&source_pos),
+                            physical_from_logical(&target_pos),
+                            &self.animation_config
+                        )
+                    )
+                );
+
+                group.add_animation(Box::new(staggered));
+                delay += self.animation_config.stagger_delay;
+            }
+        }
+
+        if !group.is_empty() {
+            let mut animation = AnimationSequence::new();
+            animation.add_animation(Box::new(group));
+            self.animation = Some(animation);
+            self.animation_speed = 1.0f32;
+        }
+
+        result
+    }
+
+
+    /// Change the board with `build`, and animate the change.
+    pub fn rebuild_board<F, E>(&mut self, build: F) -> std::result::Result<(), E>
+        where F: FnOnce(&mut GameState) -> std::result::Result<(), E>
+    {
+        self.skip_animation();
+
+        let previous_pos: Vec<_> = self.world.robots.iter()
+            .map(|robot| (robot.id, robot.pos))
+            .collect();
+
+        build(&mut self.state)?;
+        self.reset_rand_pos();
+        self.start_board_transition(previous_pos);
+        Ok(())
+    }
+
+
+    /// Use the board and targets of `built`, like when the config changed,
+    /// robots staying where they still can.
+    pub fn replace_board(&mut self, built: GameState) {
+        self.skip_animation();
+
+        let previous_pos: Vec<_> = self.world.robots.iter()
+            .map(|robot| (robot.id, robot.pos))
+            .collect();
+
+        self.state.board = built.board;
+        self.state.targets = built.targets;
+        self.state.tiling = built.tiling;
+        // Moves played may not be valid on the new board.
+        self.clear_undo_stack();
+        self.state.keep_or_reset_pos();
+        self.world.reset(&self.state);
+        self.start_board_transition(previous_pos);
+    }
+
+
+    fn start_board_transition(&mut self, previous_pos: Vec<(RobotId, Option<PhysicalPos>)>) {
+        if self.instant {
+            return;
+        }
+
+        let transition = BoardTransitionAnimation::new(
+            previous_pos, &self.state, &mut self.world, &self.animation_config);
+
+        let mut animation = AnimationSequence::new();
+        animation.add_animation(Box::new(transition));
+        self.animation = Some(animation);
+        self.animation_speed = 1.0f32;
+    }
+
+
     pub fn reset_rand_pos(&mut self) {
+        self.skip_animation();
         self.clear_undo_stack();
         self.state.reset_rand_pos();
         self.world.reset(&self.state);
@@ -122,6 +370,14 @@ impl Game {
         source_pos: &LogicalPos,
         target_pos: &LogicalPos
     ) {
+        if self.instant {
+            self.world.reset(&self.state);
+            return;
+        }
+        if !self.animated {
+            return;
+        }
+
         let mut animation = match self.animation.take() {
             Some(animation) => { animation }
             None => { AnimationSequence::new() }
@@ -129,16 +385,17 @@ impl Game {
 
         let source_pos = physical_from_logical(source_pos);
         let target_pos = physical_from_logical(target_pos);
-        let duration = 0.04 * source_pos.distance(target_pos);
 
         animation.add_animation(
             Box::new(
                 MoveRobotAnimation::new(
-                    robot, source_pos, target_pos, duration
+                    robot, source_pos, target_pos, &self.animation_config
                 )
             )
         );
-        self.animation_speed = (animation.get_duration() - animation.get_time()).sqrt();
+        self.animation_speed =
+            if self.demonstrating || !self.animation_config.accelerate_queue { 1.0f32 }
+            else { (animation.get_duration() - animation.get_time()).sqrt() };
 
         self.animation = Some(animation);
     }
@@ -149,6 +406,131 @@ impl Game {
             if animation.render(&self.state, &mut self.world, elapsed * self.animation_speed) {
                 self.animation = Some(animation);
             }
+            else {
+                self.demonstrating = false;
+            }
+        }
+    }
+
+
+    pub fn is_animating(&self) -> bool {
+        self.animation.is_some()
+    }
+
+
+    // Jump to the end of the running animation.
+    pub fn skip_animation(&mut self) {
+        if let Some(mut animation) = self.animation.take() {
+            animation.finish(&self.state, &mut self.world);
+        }
+        self.demonstrating = false;
+    }
+
+
+    // New inputs fast-forward the current animation instead of queuing after
+    // it. Demonstrations are left alone, they are played for everyone.
+    fn interrupt_animation(&mut self) {
+        if self.animation_config.interrupt_on_input && !self.demonstrating {
+            self.skip_animation();
         }
     }
 }
+
+
+#[cfg(test)]
+mod tests {
+    use crate::positionning::{Dimensions, LogicalPos, Way};
+
+    use super::{Game, RobotId};
+
+
+    // Empty 8x8 board, the red robot at the bottom and the others out of
+    // its way.
+    fn game() -> Game {
+        let mut game = Game::without_animation();
+        game.state.board.reset(&Dimensions { width: 8, height: 8 }).expect("board resets");
+        game.state.place_robot(RobotId::Red, LogicalPos { x: 3, y: 7 });
+        game.state.place_robot(RobotId::Green, LogicalPos { x: 5, y: 5 });
+        game.state.place_robot(RobotId::Blue, LogicalPos { x: 6, y: 6 });
+        game.state.place_robot(RobotId::Yellow, LogicalPos { x: 1, y: 4 });
+        game
+    }
+
+
+    fn red(game: &Game) -> LogicalPos {
+        game.state.robot_pos(RobotId::Red).expect("red robot is placed")
+    }
+
+
+    fn play(game: &mut Game, way: Way) {
+        assert!(game.try_move_robot_in_dir(RobotId::Red, way).expect("robot moves"));
+    }
+
+
+    // Red goes up, then left, then right instead: two lines ending in the
+    // top corners.
+    fn branched() -> Game {
+        let mut game = game();
+        play(&mut game, Way::Up);
+        play(&mut game, Way::Left);
+        game.undo().expect("move is undone");
+        play(&mut game, Way::Right);
+        game
+    }
+
+
+    #[test]
+    fn redo_plays_the_last_line_again() {
+        let mut game = branched();
+        game.undo_all().expect("moves are undone");
+        assert_eq!(red(&game), LogicalPos { x: 3, y: 7 });
+
+        game.redo_all().expect("moves are redone");
+        assert_eq!(red(&game), LogicalPos { x: 7, y: 0 });
+        assert_eq!(game.history().len(), 2);
+    }
+
+
+    #[test]
+    fn switch_sibling_wraps_around() {
+        let mut game = branched();
+
+        assert!(game.switch_sibling(true).expect("sibling is played"));
+        assert_eq!(red(&game), LogicalPos { x: 0, y: 0 });
+        assert!(game.switch_sibling(true).expect("sibling is played"));
+        assert_eq!(red(&game), LogicalPos { x: 7, y: 0 });
+        assert!(game.switch_sibling(false).expect("sibling is played"));
+        assert_eq!(red(&game), LogicalPos { x: 0, y: 0 });
+        assert!(game.switch_sibling(false).expect("sibling is played"));
+        assert_eq!(red(&game), LogicalPos { x: 7, y: 0 });
+
+        // The first move has no other one beside it.
+        game.undo().expect("move is undone");
+        assert!(!game.switch_sibling(true).expect("nothing to switch to"));
+        assert_eq!(red(&game), LogicalPos { x: 3, y: 0 });
+    }
+
+
+    #[test]
+    fn switch_variant_cycles_through_lines() {
+        let mut game = branched();
+        game.undo_all().expect("moves are undone");
+
+        assert_eq!(game.switch_variant().expect("variant is played"), Some((1, 2)));
+        assert_eq!(red(&game), LogicalPos { x: 0, y: 0 });
+        assert_eq!(game.switch_variant().expect("variant is played"), Some((2, 2)));
+        assert_eq!(red(&game), LogicalPos { x: 7, y: 0 });
+        assert_eq!(game.switch_variant().expect("variant is played"), Some((1, 2)));
+        assert_eq!(red(&game), LogicalPos { x: 0, y: 0 });
+    }
+
+
+    #[test]
+    fn switch_variant_needs_two_lines() {
+        let mut game = game();
+        play(&mut game, Way::Up);
+
+        assert_eq!(game.switch_variant().expect("nothing to switch to"), None);
+        assert_eq!(red(&game), LogicalPos { x: 3, y: 0 });
+    }
+}
@@ -1,6 +1,10 @@
+use rand::Rng;
+use rand::seq::SliceRandom;
+
 use crate::positionning::{LogicalPos, PosExtra, Way};
 
 use super::robot::{Robot, RobotId};
+use super::target::Target;
 
 #[allow(unused_imports)]
 use crate::board::{
@@ -8,11 +12,18 @@ use crate::board::{
     BoardByIndividualCells,
     BoardByIndirectTable,
 };
+use crate::board::Tiling;
 
 
 pub struct GameState {
     pub board: Box<dyn EditableBoard>,
     pub robots: Vec<Robot>,
+    pub target: Option<Target>,
+    /// Cells that can be targets, any free cell being one when empty.
+    pub targets: Vec<Target>,
+    /// How tiles of the board are laid out, `None` when it is not made of
+    /// tiles.
+    pub tiling: Option<Tiling>,
 }
 
 
@@ -30,7 +41,10 @@ impl GameState {
 
         GameState {
             board,
-            robots
+            robots,
+            target: None,
+            targets: Vec::new(),
+            tiling: None,
         }
     }
 
@@ -63,21 +77,83 @@ impl GameState {
 
 
     pub fn reset_rand_pos(&mut self) {
+        self.reset_rand_pos_with(&mut rand::thread_rng());
+    }
+
+
+    /// Same as `reset_rand_pos`, the same `rng` placing robots the same way.
+    pub fn reset_rand_pos_with<R>(&mut self, rng: &mut R) where R: Rng {
         // cancel previous positions
         for robot in self.robots.iter_mut() {
             robot.pos = None;
         }
 
         for i in 0..self.robots.len() {
-            self.robots[i].pos = self.find_start_pos();
+            self.robots[i].pos = self.find_start_pos_with(rng);
+        }
+    }
+
+
+    /// Keep robots where they can still start from, after the board changed,
+    /// the other ones being placed at random.
+    pub fn keep_or_reset_pos(&mut self) {
+        for i in 0..self.robots.len() {
+            let kept = self.robots[i].pos
+                .filter(|pos| self.board.is_start_pos(pos).unwrap_or(false))
+                .filter(|pos| self.robots[..i].iter().all(|robot| robot.pos != Some(*pos)));
+            self.robots[i].pos = kept;
+        }
+
+        for i in 0..self.robots.len() {
+            if self.robots[i].pos.is_none() {
+                self.robots[i].pos = self.find_start_pos();
+            }
+        }
+    }
+
+
+    pub fn reset_rand_target(&mut self) {
+        self.reset_rand_target_with(&mut rand::thread_rng());
+    }
+
+
+    pub fn reset_rand_target_with<R>(&mut self, rng: &mut R) where R: Rng {
+        // Targets of the board, unless a robot already stands on them.
+        let free_targets: Vec<&Target> = self.targets.iter()
+            .filter(|target| self.robots.iter()
+                    .all(|robot| robot.pos.as_ref() != Some(&target.pos)))
+            .collect();
+        if let Some(target) = free_targets.choose(rng) {
+            self.target = Some((*target).clone());
+            return;
+        }
+        let robot = self.robots.choose(rng).map(|robot| robot.id);
+
+        self.target = robot
+            .and_then(|robot| self.find_start_pos_with(rng).map(|pos| Target::new(robot, pos)));
+    }
+
+
+    pub fn is_target_reached(&self) -> bool {
+        match self.target {
+            Some(ref target) => self.robot_pos(target.robot).as_ref() == Some(&target.pos),
+            None => false,
         }
     }
 
 
     pub fn find_start_pos(&self) -> Option<LogicalPos> {
-        let side_length = self.board.side_length().0;
+        self.find_start_pos_with(&mut rand::thread_rng())
+    }
+
+
+    pub fn find_start_pos_with<R>(&self, rng: &mut R) -> Option<LogicalPos> where R: Rng {
+        let dimensions = self.board.dimensions();
         (0..1000)
-            .map(|_| PosExtra::rand(side_length, side_length))
+            .map(|_| LogicalPos {
+                x: rng.gen_range(0, dimensions.width),
+                y: rng.gen_range(0, dimensions.height),
+            })
             .filter(|pos| self.board.is_start_pos(pos).unwrap_or(false))
             .filter(|pos| self.robots.iter().all(|r| match r.pos {
                 Some(ref p) if p == pos => false,
@@ -88,6 +164,17 @@ impl GameState {
 
 
     pub fn cast_ray(&self, source_pos: &LogicalPos, way: Way) -> LogicalPos {
+        self.cast_ray_ignoring(source_pos, way, None)
+    }
+
+
+    /// Like `cast_ray`, `ignored` being out of the way wherever it stands,
+    /// as if it were the robot moving.
+    pub fn cast_ray_ignoring(
+            &self,
+            source_pos: &LogicalPos,
+            way: Way,
+            ignored: Option<RobotId>) -> LogicalPos {
         let mut hits = vec![
             self.board.hit_from(&source_pos, way)
                 .expect("board can at least hit the wall")
@@ -96,6 +183,7 @@ impl GameState {
         hits.extend(
             self.robots.iter()
             .filter(|robot| robot.pos.as_ref() != Some(source_pos))
+            .filter(|robot| Some(robot.id) != ignored)
             .filter_map(|robot| robot.pos.as_ref())
             .filter_map(|pos| source_pos.find_hit_to(&pos, way))
         );
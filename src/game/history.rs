@@ -0,0 +1,251 @@
+use std::rc::Rc;
+
+use super::command::{Command, SerializedCommand};
+
+
+/// Every line of moves explored since the last reset. Playing a new move
+/// after undoing starts a new branch instead of forgetting the undone ones.
+pub struct HistoryTree {
+    // The root stands for the position where the round started, and has no
+    // command.
+    nodes: Vec<Node>,
+    current: usize,
+}
+
+
+struct Node {
+    command: Option<Rc<dyn Command>>,
+    parent: usize,
+    children: Vec<usize>,
+    // Child played again by redo, the last one visited.
+    next: Option<usize>,
+}
+
+
+const ROOT: usize = 0;
+
+
+impl HistoryTree {
+    pub fn new() -> HistoryTree {
+        HistoryTree {
+            nodes: vec![Node { command: None, parent: ROOT, children: Vec::new(), next: None }],
+            current: ROOT,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        *self = HistoryTree::new();
+    }
+
+    pub fn current(&self) -> usize {
+        self.current
+    }
+
+    /// Command that led to the current position, `None` at the root.
+    pub fn current_command(&self) -> Option<Rc<dyn Command>> {
+        self.nodes[self.current].command.clone()
+    }
+
+    pub fn command(&self, node: usize) -> Option<Rc<dyn Command>> {
+        self.nodes[node].command.clone()
+    }
+
+    /// Move played by redo from the current position.
+    pub fn next(&self) -> Option<usize> {
+        self.nodes[self.current].next
+    }
+
+    /// Child of the current position playing `command`, if it was played
+    /// before.
+    pub fn find_child(&self, command: &SerializedCommand) -> Option<usize> {
+        self.nodes[self.current].children.iter()
+            .cloned()
+            .find(|&child| self.nodes[child].command.as_ref()
+                  .is_some_and(|played| played.serialize() == *command))
+    }
+
+    /// Add `command` after the current position, and go to it.
+    pub fn push(&mut self, command: Rc<dyn Command>) {
+        let node = self.nodes.len();
+        self.nodes.push(Node {
+            command: Some(command),
+            parent: self.current,
+            children: Vec::new(),
+            next: None,
+        });
+        self.nodes[self.current].children.push(node);
+        self.enter(node);
+    }
+
+    /// Go back to the parent of the current position, which redo returns
+    /// from.
+    pub fn leave(&mut self) {
+        self.current = self.nodes[self.current].parent;
+    }
+
+    /// Go to a child of the current position, redo following it from now
+    /// on.
+    pub fn enter(&mut self, child: usize) {
+        self.set_next(child);
+        self.current = child;
+    }
+
+    /// Make redo play `child` of the current position.
+    pub fn set_next(&mut self, child: usize) {
+        self.nodes[self.current].next = Some(child);
+    }
+
+    /// Child of the current position on the way to `node`, `None` when
+    /// `node` is not after it.
+    pub fn child_towards(&self, mut node: usize) -> Option<usize> {
+        while node != ROOT {
+            let parent = self.nodes[node].parent;
+            if parent == self.current {
+                return Some(node);
+            }
+            node = parent;
+        }
+        None
+    }
+
+    /// Positions branching from the same one as the current position, itself
+    /// included, in the order they were first played.
+    pub fn siblings(&self) -> &[usize] {
+        if self.current == ROOT {
+            return &[];
+        }
+        &self.nodes[self.nodes[self.current].parent].children
+    }
+
+    /// Last position of every line explored, in the order they were
+    /// started.
+    pub fn variants(&self) -> Vec<usize> {
+        (0..self.nodes.len())
+            .filter(|&node| self.nodes[node].children.is_empty())
+            .collect()
+    }
+
+    /// Where redo leads from the current position, when repeated.
+    pub fn variant(&self) -> usize {
+        let mut node = self.current;
+        while let Some(next) = self.nodes[node].next {
+            node = next;
+        }
+        node
+    }
+
+    /// Commands from the root to the current position.
+    pub fn commands(&self) -> Vec<SerializedCommand> {
+        let mut commands = Vec::new();
+        let mut node = self.current;
+        while node != ROOT {
+            if let Some(ref command) = self.nodes[node].command {
+                commands.push(command.serialize());
+            }
+            node = self.nodes[node].parent;
+        }
+        commands.reverse();
+        commands
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use crate::positionning::LogicalPos;
+    use crate::game::RobotId;
+    use crate::game::command::CommandBase;
+    use crate::game::move_robot_command::MoveRobotCommand;
+
+    use super::{HistoryTree, ROOT};
+
+
+    // Red robot moving from the top left corner to `x`, each `x` making a
+    // different command.
+    fn command(x: usize) -> Rc<MoveRobotCommand> {
+        Rc::new(MoveRobotCommand::new(
+            RobotId::Red, LogicalPos { x: 0, y: 0 }, LogicalPos { x, y: 0 }))
+    }
+
+
+    // Two moves, the second one undone and replaced by another:
+    //
+    //     root - 1 - 2
+    //              \ 3
+    fn branched() -> HistoryTree {
+        let mut history = HistoryTree::new();
+        history.push(command(1));
+        history.push(command(2));
+        history.leave();
+        history.push(command(3));
+        history
+    }
+
+
+    #[test]
+    fn new_move_after_undo_starts_a_sibling() {
+        let history = branched();
+
+        assert_eq!(history.current(), 3);
+        assert_eq!(history.siblings(), &[2, 3]);
+        assert_eq!(history.variants(), vec![2, 3]);
+        assert_eq!(history.commands(), vec![command(1).serialize(), command(3).serialize()]);
+    }
+
+
+    #[test]
+    fn move_played_again_is_found_among_children() {
+        let mut history = branched();
+        history.leave();
+
+        assert_eq!(history.find_child(&command(2).serialize()), Some(2));
+        assert_eq!(history.find_child(&command(3).serialize()), Some(3));
+        assert_eq!(history.find_child(&command(4).serialize()), None);
+    }
+
+
+    #[test]
+    fn redo_follows_the_last_visited_child() {
+        let mut history = branched();
+        history.leave();
+        assert_eq!(history.next(), Some(3));
+
+        history.enter(2);
+        history.leave();
+        assert_eq!(history.next(), Some(2));
+
+        history.leave();
+        assert_eq!(history.current(), ROOT);
+        assert_eq!(history.next(), Some(1));
+        assert_eq!(history.variant(), 2);
+    }
+
+
+    #[test]
+    fn child_towards_leads_to_later_positions_only() {
+        let mut history = branched();
+        history.leave();
+        history.leave();
+
+        assert_eq!(history.child_towards(3), Some(1));
+        history.enter(1);
+        assert_eq!(history.child_towards(3), Some(3));
+        assert_eq!(history.child_towards(ROOT), None);
+        history.enter(3);
+        assert_eq!(history.child_towards(2), None);
+    }
+
+
+    #[test]
+    fn clear_forgets_every_line() {
+        let mut history = branched();
+        history.clear();
+
+        assert_eq!(history.current(), ROOT);
+        assert!(history.siblings().is_empty());
+        assert!(history.commands().is_empty());
+        assert_eq!(history.next(), None);
+    }
+}
@@ -38,6 +38,10 @@ impl KeyboardController {
         }
     }
 
+    pub fn current_robot(&self) -> RobotId {
+        self.current_robot
+    }
+
     pub fn process_event(&mut self, game: &mut Game, event: &Event) -> Result<bool> {
         match event {
             Event::KeyDown { scancode: Some(scancode), repeat: false, .. } => {
@@ -56,7 +60,6 @@ impl KeyboardController {
         }
         else if let Some(robot) = self.robot_from_scancode(scancode) {
             self.current_robot = robot;
-            println!("Switch to robot {:?}", robot);
             Ok(true)
         }
         else {
@@ -1,17 +1,29 @@
 pub mod error;
 
 mod robot;
+mod target;
 mod game_state;
 mod command;
+mod history;
 mod move_robot_command;
+mod easing;
 mod animation;
+mod transition;
 mod game;
+#[cfg(feature = "sdl")]
 mod keyboard_controller;
 mod world;
+mod reachability;
 
 
 pub use self::robot::RobotId;
+pub use self::target::Target;
+pub use self::easing::Easing;
 pub use self::game_state::GameState;
+pub use self::command::SerializedCommand;
+pub use self::move_robot_command::MoveRobotCommand;
 pub use self::game::Game;
+#[cfg(feature = "sdl")]
 pub use self::keyboard_controller::KeyboardController;
 pub use self::world::World;
+pub use self::reachability::Reachability;
@@ -1,12 +1,15 @@
-use crate::positionning::LogicalPos;
+use serde_derive::{Serialize, Deserialize};
+
+use crate::positionning::{LogicalPos, Way};
 
 use super::error::*;
 use super::robot::RobotId;
 use super::Game;
-use super::command::{Command, CommandBase};
+use super::game_state::GameState;
+use super::command::{Command, CommandBase, SerializedCommand};
 
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct MoveRobotCommand {
     robot: RobotId,
     source_pos: LogicalPos,
@@ -25,6 +28,19 @@ impl MoveRobotCommand {
             target_pos,
         }
     }
+
+    // The robot must start from its current position and slide until it hits
+    // something, like a player would move it.
+    pub fn is_valid_on(&self, state: &GameState) -> bool {
+        if state.robot_pos(self.robot).as_ref() != Some(&self.source_pos) {
+            return false;
+        }
+
+        [Way::Up, Way::Down, Way::Left, Way::Right]
+            .iter()
+            .map(|&way| state.cast_ray(&self.source_pos, way))
+            .any(|pos| pos != self.source_pos && pos == self.target_pos)
+    }
 }
 
 
@@ -40,6 +56,10 @@ impl CommandBase for MoveRobotCommand {
         game.start_move_animation(self.robot, &self.target_pos, &self.source_pos);
         Ok(())
     }
+
+    fn serialize(&self) -> SerializedCommand {
+        SerializedCommand::MoveRobot(self.clone())
+    }
 }
 
 
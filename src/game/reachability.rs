@@ -0,0 +1,94 @@
+use std::collections::VecDeque;
+
+use crate::positionning::{LogicalPos, Dimensions, Way};
+
+use super::robot::RobotId;
+use super::game_state::GameState;
+
+
+const WAYS: [Way; 4] = [Way::Up, Way::Down, Way::Left, Way::Right];
+
+
+/// Least number of moves for a robot to stop on each cell of the board,
+/// other robots staying where they are.
+#[derive(Debug)]
+pub struct Reachability {
+    robot: RobotId,
+    // Robots as they were, to tell when moves are to be computed again.
+    positions: Vec<Option<LogicalPos>>,
+    dimensions: Dimensions,
+    // By row, none for cells the robot can never stop on.
+    moves: Vec<Option<usize>>,
+}
+
+
+impl Reachability {
+    /// Explore moves of `robot` breadth first, none if it is not on the
+    /// board.
+    pub fn compute(state: &GameState, robot: RobotId) -> Option<Reachability> {
+        let start = state.robot_pos(robot)?;
+        let dimensions = state.board.dimensions();
+        if !dimensions.contains(&start) {
+            return None;
+        }
+
+        let mut reachability = Reachability {
+            robot,
+            positions: Self::positions_of(state),
+            dimensions,
+            moves: vec![None; dimensions.width * dimensions.height],
+        };
+        reachability.set(&start, 0);
+
+        let mut pending = VecDeque::new();
+        pending.push_back((start, 0));
+
+        while let Some((pos, moves)) = pending.pop_front() {
+            for way in WAYS.iter() {
+                let next = state.cast_ray_ignoring(&pos, *way, Some(robot));
+                if next != pos && reachability.moves_to(&next).is_none() {
+                    reachability.set(&next, moves + 1);
+                    pending.push_back((next, moves + 1));
+                }
+            }
+        }
+
+        Some(reachability)
+    }
+
+
+    /// Whether moves were computed for `robot` with robots where they are in
+    /// `state`, the board being assumed to be the same.
+    pub fn is_up_to_date(&self, state: &GameState, robot: RobotId) -> bool {
+        self.robot == robot && self.positions == Self::positions_of(state)
+    }
+
+
+    pub fn dimensions(&self) -> Dimensions {
+        self.dimensions
+    }
+
+
+    pub fn moves_to(&self, pos: &LogicalPos) -> Option<usize> {
+        if self.dimensions.contains(pos) {
+            self.moves[pos.y * self.dimensions.width + pos.x]
+        }
+        else { None }
+    }
+
+
+    /// Moves needed for the farthest reachable cell.
+    pub fn max_moves(&self) -> usize {
+        self.moves.iter().filter_map(|moves| *moves).max().unwrap_or(0)
+    }
+
+
+    fn positions_of(state: &GameState) -> Vec<Option<LogicalPos>> {
+        state.robots.iter().map(|robot| robot.pos).collect()
+    }
+
+
+    fn set(&mut self, pos: &LogicalPos, moves: usize) {
+        self.moves[pos.y * self.dimensions.width + pos.x] = Some(moves);
+    }
+}
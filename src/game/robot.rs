@@ -1,8 +1,10 @@
+use serde_derive::{Serialize, Deserialize};
+
 use crate::positionning::LogicalPos;
 
 
 /// Robot identity (designated by color, like ghost in pacman)
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
 pub enum RobotId {
     Red,
     Green,
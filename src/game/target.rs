@@ -0,0 +1,20 @@
+use serde_derive::{Serialize, Deserialize};
+
+use crate::positionning::LogicalPos;
+
+use super::robot::RobotId;
+
+
+/// Cell a given robot has to reach to end the round.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Target {
+    pub robot: RobotId,
+    pub pos: LogicalPos,
+}
+
+
+impl Target {
+    pub fn new(robot: RobotId, pos: LogicalPos) -> Target {
+        Target { robot, pos }
+    }
+}
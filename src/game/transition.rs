@@ -0,0 +1,329 @@
+use cgmath::prelude::*;
+use cgmath::Vector2;
+
+use crate::positionning::PhysicalPos;
+use crate::config::AnimationConfig;
+use crate::board::{Border, Tiling};
+
+use super::easing::Easing;
+use super::robot::RobotId;
+use super::world::{World, QuadrantState};
+use super::game_state::GameState;
+use super::animation::{
+    Animation,
+    AnimationSequence,
+    DelayAnimation,
+    ParallelAnimation,
+};
+
+
+// Quarter turn a quadrant does while sliding in.
+const QUADRANT_START_ANGLE: f32 = -90f32;
+
+
+/// Show a robot, or hide it, at a fixed position.
+pub struct FadeRobotAnimation {
+    robot_id: RobotId,
+    pos: PhysicalPos,
+    source_alpha: f32,
+    target_alpha: f32,
+    time: f32,
+    duration: f32,
+}
+
+
+impl FadeRobotAnimation {
+    pub fn new(
+            robot_id: RobotId,
+            pos: PhysicalPos,
+            source_alpha: f32,
+            target_alpha: f32,
+            duration: f32) -> FadeRobotAnimation {
+        FadeRobotAnimation {
+            robot_id,
+            pos,
+            source_alpha,
+            target_alpha,
+            time: 0f32,
+            duration,
+        }
+    }
+
+    fn apply(&self, state: &GameState, world: &mut World, alpha: f32) {
+        if let Some(robot_index) = state.robot_index(self.robot_id) {
+            let robot = &mut world.robots[robot_index];
+            robot.pos = Some(self.pos);
+            robot.alpha = alpha;
+        }
+    }
+}
+
+
+impl Animation for FadeRobotAnimation {
+    fn get_time(&self) -> f32 {
+        self.time
+    }
+
+    fn get_duration(&self) -> f32 {
+        self.duration
+    }
+
+    fn render(
+        &mut self,
+        state: &GameState,
+        world: &mut World,
+        elapsed: f32
+    ) -> bool {
+        self.time += elapsed;
+
+        if self.time < self.duration {
+            let t = self.time / self.duration;
+            let alpha = self.source_alpha + (self.target_alpha - self.source_alpha) * t;
+            self.apply(state, world, alpha);
+            true
+        }
+        else {
+            self.apply(state, world, self.target_alpha);
+            false
+        }
+    }
+
+    fn finish(&mut self, state: &GameState, world: &mut World) {
+        self.time = self.duration;
+        self.apply(state, world, self.target_alpha);
+    }
+}
+
+
+/// Bring a quarter of the board from outside to its place, turning.
+pub struct QuadrantAnimation {
+    border: Border,
+    easing: Easing,
+    time: f32,
+    duration: f32,
+}
+
+
+impl QuadrantAnimation {
+    pub fn new(border: Border, easing: Easing, duration: f32) -> QuadrantAnimation {
+        QuadrantAnimation {
+            border,
+            easing,
+            time: 0f32,
+            duration,
+        }
+    }
+
+    // Where the quadrant waits before moving.
+    pub fn initial_state(border: &Border) -> QuadrantState {
+        QuadrantState {
+            offset: border.outward() * 0.5f32,
+            angle: QUADRANT_START_ANGLE,
+        }
+    }
+
+    fn apply(&self, world: &mut World, t: f32) {
+        let initial = Self::initial_state(&self.border);
+
+        if let Some(quadrant) = world.quadrants.as_mut()
+            .and_then(|quadrants| quadrants.get_mut(self.border.index())) {
+            quadrant.offset = initial.offset.lerp(Vector2::new(0f32, 0f32), t);
+            quadrant.angle = initial.angle * (1f32 - t);
+        }
+    }
+}
+
+
+impl Animation for QuadrantAnimation {
+    fn get_time(&self) -> f32 {
+        self.time
+    }
+
+    fn get_duration(&self) -> f32 {
+        self.duration
+    }
+
+    fn render(
+        &mut self,
+        _state: &GameState,
+        world: &mut World,
+        elapsed: f32
+    ) -> bool {
+        self.time += elapsed;
+
+        if self.time < self.duration {
+            let t = self.easing.apply(self.time / self.duration);
+            self.apply(world, t);
+            true
+        }
+        else {
+            self.apply(world, 1f32);
+            false
+        }
+    }
+
+    fn finish(&mut self, _state: &GameState, world: &mut World) {
+        self.time = self.duration;
+        self.apply(world, 1f32);
+    }
+}
+
+
+/// Show the new board, fading in from nothing, when it is not made of
+/// quadrants.
+pub struct FadeBoardAnimation {
+    time: f32,
+    duration: f32,
+}
+
+
+impl FadeBoardAnimation {
+    pub fn new(duration: f32) -> FadeBoardAnimation {
+        FadeBoardAnimation {
+            time: 0f32,
+            duration,
+        }
+    }
+}
+
+
+impl Animation for FadeBoardAnimation {
+    fn get_time(&self) -> f32 {
+        self.time
+    }
+
+    fn get_duration(&self) -> f32 {
+        self.duration
+    }
+
+    fn render(
+        &mut self,
+        _state: &GameState,
+        world: &mut World,
+        elapsed: f32
+    ) -> bool {
+        self.time += elapsed;
+
+        if self.time < self.duration {
+            world.board_alpha = Some(self.time / self.duration);
+            true
+        }
+        else {
+            world.board_alpha = Some(1f32);
+            false
+        }
+    }
+
+    fn finish(&mut self, _state: &GameState, world: &mut World) {
+        self.time = self.duration;
+        world.board_alpha = Some(1f32);
+    }
+}
+
+
+/// Robots leave the old board, the new one is put in place quadrant by
+/// quadrant (or fades in when its tiles are laid out otherwise), then robots
+/// show up on it.
+pub struct BoardTransitionAnimation {
+    sequence: AnimationSequence,
+}
+
+
+impl BoardTransitionAnimation {
+    // Prepare `world` for the first frame, robots being still displayed at
+    // `previous_pos` and the new board being out of sight.
+    pub fn new(
+            previous_pos: Vec<(RobotId, Option<PhysicalPos>)>,
+            state: &GameState,
+            world: &mut World,
+            config: &AnimationConfig) -> BoardTransitionAnimation {
+        let fade_duration = config.fade_duration;
+        let quadrant_duration = config.quadrant_duration;
+
+        let mut fade_out = ParallelAnimation::new();
+        for (robot, pos) in previous_pos.into_iter() {
+            if let Some(pos) = pos {
+                fade_out.add_animation(Box::new(
+                        FadeRobotAnimation::new(robot, pos, 1f32, 0f32, fade_duration)));
+            }
+        }
+
+        // Quarters of other boards would cut through their tiles.
+        let in_quadrants = matches!(
+            state.tiling, Some(Tiling::Quadrants) | Some(Tiling::QuadrantsWithGap));
+
+        let mut slide_in = ParallelAnimation::new();
+        if in_quadrants {
+            for (i, border) in Border::all().iter().enumerate() {
+                let mut staggered = AnimationSequence::new();
+                staggered.add_animation(Box::new(
+                        DelayAnimation::new(i as f32 * config.stagger_delay)));
+                staggered.add_animation(Box::new(
+                        QuadrantAnimation::new(*border, config.easing, quadrant_duration)));
+                slide_in.add_animation(Box::new(staggered));
+            }
+        }
+        else {
+            slide_in.add_animation(Box::new(FadeBoardAnimation::new(quadrant_duration)));
+        }
+
+        let mut fade_in = ParallelAnimation::new();
+        for robot in world.robots.iter_mut() {
+            if let Some(pos) = robot.pos {
+                fade_in.add_animation(Box::new(
+                        FadeRobotAnimation::new(robot.id, pos, 0f32, 1f32, fade_duration)));
+            }
+            robot.alpha = 0f32;
+        }
+
+        if in_quadrants {
+            world.quadrants = Some(Border::all().iter()
+                .map(QuadrantAnimation::initial_state)
+                .collect());
+        }
+        else {
+            world.board_alpha = Some(0f32);
+        }
+
+        let mut sequence = AnimationSequence::new();
+        sequence.add_animation(Box::new(fade_out));
+        sequence.add_animation(Box::new(slide_in));
+        sequence.add_animation(Box::new(fade_in));
+
+        // Show robots at their previous position until they fade out.
+        sequence.render(state, world, 0f32);
+
+        BoardTransitionAnimation { sequence }
+    }
+}
+
+
+impl Animation for BoardTransitionAnimation {
+    fn get_time(&self) -> f32 {
+        self.sequence.get_time()
+    }
+
+    fn get_duration(&self) -> f32 {
+        self.sequence.get_duration()
+    }
+
+    fn render(
+        &mut self,
+        state: &GameState,
+        world: &mut World,
+        elapsed: f32
+    ) -> bool {
+        let running = self.sequence.render(state, world, elapsed);
+        if !running {
+            world.quadrants = None;
+            world.board_alpha = None;
+        }
+        running
+    }
+
+    fn finish(&mut self, state: &GameState, world: &mut World) {
+        self.sequence.finish(state, world);
+        world.quadrants = None;
+        world.board_alpha = None;
+    }
+}
@@ -2,18 +2,38 @@ use super::RobotId;
 use super::GameState;
 use super::robot::Robot;
 
+use cgmath::Vector2;
+
 use crate::positionning::{PhysicalPos, physical_from_logical};
 
 #[derive(Debug, Clone)]
 pub struct RobotState {
     pub id: RobotId,
     pub pos: Option<PhysicalPos>,
+    // Relative size of the robot, on each axis.
+    pub scale: Vector2<f32>,
+    // Opacity, from 0 (invisible) to 1.
+    pub alpha: f32,
+}
+
+
+/// Placement of one quarter of the board while it moves into place.
+#[derive(Debug, Clone)]
+pub struct QuadrantState {
+    // Displacement, as a fraction of the board size.
+    pub offset: Vector2<f32>,
+    // Rotation around its center, in degrees.
+    pub angle: f32,
 }
 
 
 #[derive(Debug)]
 pub struct World {
     pub robots: Vec<RobotState>,
+    // Indexed by `Border::index`, only set while the board is moving.
+    pub quadrants: Option<Vec<QuadrantState>>,
+    // Opacity of a board not made of quadrants, only set while it fades in.
+    pub board_alpha: Option<f32>,
 }
 
 
@@ -24,6 +44,8 @@ impl From<&Robot> for RobotState {
         Self {
             id: robot.id,
             pos,
+            scale: Vector2::new(1f32, 1f32),
+            alpha: 1f32,
         }
     }
 }
@@ -33,10 +55,19 @@ impl World {
     pub fn new() -> World {
         World {
             robots: Vec::new(),
+            quadrants: None,
+            board_alpha: None,
         }
     }
 
 
+    /// Whether the board is being put in place, things on it being hidden
+    /// meanwhile.
+    pub fn is_board_moving(&self) -> bool {
+        self.quadrants.is_some() || self.board_alpha.is_some()
+    }
+
+
     pub fn reset(&mut self, game_state: &GameState) {
         self.robots = game_state.robots.iter()
             .map(|robot| robot.into())
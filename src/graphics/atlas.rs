@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use serde_derive::Deserialize;
+
+use super::error::*;
+
+
+/// Content of an atlas file: where to find each sprite in a set of images.
+#[derive(Debug, Deserialize)]
+pub struct AtlasDescriptor {
+    pub images: Vec<ImageDescriptor>,
+    pub sprites: HashMap<String, SpriteDescriptor>,
+}
+
+
+/// Paths are relative to the atlas file.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum ImageDescriptor {
+    Raster(PathBuf),
+    /// Vector version of a raster image, both having the same layout. Sprite
+    /// rectangles are given in pixels of the raster one.
+    WithSvg {
+        path: PathBuf,
+        svg: PathBuf,
+    },
+}
+
+
+impl ImageDescriptor {
+    pub fn path(&self) -> &Path {
+        match self {
+            ImageDescriptor::Raster(path) => path,
+            ImageDescriptor::WithSvg { path, .. } => path,
+        }
+    }
+
+    pub fn svg(&self) -> Option<&Path> {
+        match self {
+            ImageDescriptor::Raster(_) => None,
+            ImageDescriptor::WithSvg { svg, .. } => Some(svg),
+        }
+    }
+
+    fn relative_to(self, base: &Path) -> ImageDescriptor {
+        match self {
+            ImageDescriptor::Raster(path) => ImageDescriptor::Raster(base.join(path)),
+            ImageDescriptor::WithSvg { path, svg } => ImageDescriptor::WithSvg {
+                path: base.join(path),
+                svg: base.join(svg),
+            },
+        }
+    }
+}
+
+
+#[derive(Debug, Deserialize)]
+pub struct SpriteDescriptor {
+    #[serde(default)]
+    pub image: usize,
+    pub rect: RectDescriptor,
+    /// Point of the sprite kept in place when scaled, relative to its size.
+    #[serde(default = "defaults::pivot")]
+    pub pivot: [f32; 2],
+    #[serde(default = "defaults::scale")]
+    pub scale: f32,
+}
+
+
+#[derive(Debug, Deserialize)]
+pub struct RectDescriptor {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+
+mod defaults {
+    pub fn pivot() -> [f32; 2] { [0.5, 0.5] }
+    pub fn scale() -> f32 { 1.0 }
+}
+
+
+impl AtlasDescriptor {
+    pub fn load(path: &Path) -> Result<AtlasDescriptor> {
+        let mut content = String::new();
+        File::open(path)?.read_to_string(&mut content)?;
+
+        let mut atlas: AtlasDescriptor = json5::from_str(content.as_str())?;
+
+        let base = path.parent().unwrap_or_else(|| Path::new("."));
+        atlas.images = atlas.images.into_iter()
+            .map(|image| image.relative_to(base))
+            .collect();
+
+        for (name, sprite) in atlas.sprites.iter() {
+            if sprite.image >= atlas.images.len() {
+                bail!(ErrorKind::InvalidAtlasImage(name.clone(), sprite.image));
+            }
+        }
+
+        Ok(atlas)
+    }
+}
@@ -0,0 +1,119 @@
+use sdl2::pixels::Color;
+use sdl2::rect::{Point, Rect};
+use sdl2::render::Canvas;
+use sdl2::video::Window;
+
+use crate::positionning::LogicalPos;
+use crate::moves::MovePossibility;
+
+use super::error::*;
+
+
+// Size of side markers, relative to the cell.
+const MARKER_LENGTH: f32 = 0.3f32;
+const MARKER_THICKNESS: f32 = 0.08f32;
+
+const TEXT_COLOR: Color = Color { r: 255, g: 255, b: 255, a: 255 };
+const OPEN_COLOR: Color = Color { r: 40, g: 200, b: 40, a: 255 };
+const BLOCKED_COLOR: Color = Color { r: 230, g: 30, b: 30, a: 255 };
+
+// Segments lit for each digit, from the top one clockwise, then the middle
+// one.
+const DIGITS: [[bool; 7]; 10] = [
+    [true, true, true, true, true, true, false],
+    [false, true, true, false, false, false, false],
+    [true, true, false, true, true, false, true],
+    [true, true, true, true, false, false, true],
+    [false, true, true, false, false, true, true],
+    [true, false, true, true, false, true, true],
+    [true, false, true, true, true, true, true],
+    [true, true, true, false, false, false, false],
+    [true, true, true, true, true, true, true],
+    [true, true, true, true, false, true, true],
+];
+
+
+/// Draw what `moves_from` tells about the cell at `pos`: its coordinates in
+/// the top-left corner, a marker on each side, green when a robot can go
+/// that way and red otherwise, and a cross when it is forbidden.
+pub fn paint_cell_data(
+    canvas: &mut Canvas<Window>,
+    pos: &LogicalPos,
+    moves: &MovePossibility,
+    area: Rect,
+    ) -> Result<()> {
+    let digit_height = (area.height() / 4).max(3);
+    let digit_width = (digit_height / 2).max(2);
+    let margin = (area.width() / 10) as i32;
+    let spacing = digit_height as i32 + margin / 2;
+
+    canvas.set_draw_color(TEXT_COLOR);
+    paint_number(canvas, pos.x, Point::new(area.x() + margin, area.y() + margin),
+                 digit_width, digit_height)?;
+    paint_number(canvas, pos.y, Point::new(area.x() + margin, area.y() + margin + spacing),
+                 digit_width, digit_height)?;
+
+    let length = ((area.width().min(area.height()) as f32 * MARKER_LENGTH) as u32).max(1);
+    let thickness = ((area.width().min(area.height()) as f32 * MARKER_THICKNESS) as u32).max(1);
+    let center = area.center();
+    let markers = [
+        (moves.up, Rect::new(center.x() - length as i32 / 2, area.top(), length, thickness)),
+        (moves.down, Rect::new(
+                center.x() - length as i32 / 2, area.bottom() - thickness as i32,
+                length, thickness)),
+        (moves.left, Rect::new(area.left(), center.y() - length as i32 / 2, thickness, length)),
+        (moves.right, Rect::new(
+                area.right() - thickness as i32, center.y() - length as i32 / 2,
+                thickness, length)),
+    ];
+    for &(open, marker) in markers.iter() {
+        canvas.set_draw_color(if open { OPEN_COLOR } else { BLOCKED_COLOR });
+        canvas.fill_rect(marker).into_sdl_error()?;
+    }
+
+    if moves.forbidden {
+        canvas.set_draw_color(BLOCKED_COLOR);
+        canvas.draw_line(area.top_left(), area.bottom_right()).into_sdl_error()?;
+        canvas.draw_line(area.bottom_left(), area.top_right()).into_sdl_error()?;
+    }
+
+    Ok(())
+}
+
+
+// Digits of `value` from `origin` rightward, in the current draw color.
+fn paint_number(
+    canvas: &mut Canvas<Window>,
+    value: usize,
+    origin: Point,
+    width: u32,
+    height: u32,
+    ) -> Result<()> {
+    let advance = width as i32 + (width as i32 / 2).max(1);
+
+    for (i, digit) in value.to_string().bytes().enumerate() {
+        let segments = DIGITS[(digit - b'0') as usize];
+        let left = origin.x() + i as i32 * advance;
+        let right = left + width as i32;
+        let top = origin.y();
+        let middle = top + height as i32 / 2;
+        let bottom = top + height as i32;
+
+        let lines = [
+            (Point::new(left, top), Point::new(right, top)),
+            (Point::new(right, top), Point::new(right, middle)),
+            (Point::new(right, middle), Point::new(right, bottom)),
+            (Point::new(left, bottom), Point::new(right, bottom)),
+            (Point::new(left, middle), Point::new(left, bottom)),
+            (Point::new(left, top), Point::new(left, middle)),
+            (Point::new(left, middle), Point::new(right, middle)),
+        ];
+        for (&lit, &(start, end)) in segments.iter().zip(lines.iter()) {
+            if lit {
+                canvas.draw_line(start, end).into_sdl_error()?;
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -4,7 +4,7 @@ use std::rc::Rc;
 use sdl2::rect::Rect;
 use sdl2::render::{Canvas, TextureCreator};
 use sdl2::video::{Window, WindowContext};
-use sdl2::pixels::PixelFormatEnum;
+use sdl2::pixels::{Color, PixelFormatEnum};
 
 use crate::positionning::{RotateAngle, FlipAxis};
 
@@ -35,7 +35,53 @@ impl<'c, 't> DrawContext<'c, 't> {
         let sprite = tm.get_sprite(id)?;
         let texture = tm.get_texture(sprite)?;
         self.canvas
-            .copy(texture, sprite.geom, area)
+            .copy(texture, sprite.geom, sprite.place(area))
+            .into_sdl_error()
+    }
+
+    // Draw the sprite multiplied by `color`, with some transparency.
+    pub fn draw_tinted(
+        &mut self,
+        id: &SpriteId,
+        area: Rect,
+        color: Color,
+        alpha: u8,
+        ) -> Result<()> {
+        let mut tm = self.tm.borrow_mut();
+        let sprite = tm.get_sprite(id)?.clone();
+        let texture = tm.get_texture_mut(&sprite)?;
+
+        texture.set_color_mod(color.r, color.g, color.b);
+        texture.set_alpha_mod(alpha);
+        let result = self.canvas
+            .copy(texture, sprite.geom, sprite.place(area))
+            .into_sdl_error();
+        texture.set_alpha_mod(255);
+        texture.set_color_mod(255, 255, 255);
+
+        result
+    }
+
+    // Draw only `part` of the sprite (relative to its top-left corner),
+    // turned by `angle` degrees clockwise around the center of `area`.
+    pub fn draw_part(
+        &mut self,
+        id: &SpriteId,
+        part: Rect,
+        area: Rect,
+        angle: f64,
+        ) -> Result<()> {
+        let tm = self.tm.borrow();
+        let sprite = tm.get_sprite(id)?;
+        let texture = tm.get_texture(sprite)?;
+        let source = Rect::new(
+            sprite.geom.x() + part.x(),
+            sprite.geom.y() + part.y(),
+            part.width(),
+            part.height());
+
+        self.canvas
+            .copy_ex(texture, source, area, angle, None, false, false)
             .into_sdl_error()
     }
 
@@ -64,7 +110,7 @@ impl<'c, 't> DrawContext<'c, 't> {
         let sprite = tm.get_sprite(id)?;
         let texture = tm.get_texture(sprite)?;
         self.canvas
-            .copy_ex(texture, sprite.geom, area,
+            .copy_ex(texture, sprite.geom, sprite.place(area),
                      angle, center, flip_horizontal, flip_vertical)
             .into_sdl_error()
     }
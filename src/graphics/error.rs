@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use error_chain::error_chain;
 pub use error_chain::bail; // Re-export
 
@@ -11,6 +13,11 @@ error_chain! {
         Error, ErrorKind, ResultExt, Result;
     }
 
+    foreign_links {
+        Io(std::io::Error);
+        Json5(json5::Error);
+    }
+
     errors {
         //
         MissingTexture(index: usize) {
@@ -23,6 +30,16 @@ error_chain! {
             display("missing sprite (id={:?})", id),
         }
 
+        InvalidAtlasImage(sprite: String, image: usize) {
+            description("sprite refers to an unknown atlas image"),
+            display("sprite '{}' refers to unknown atlas image #{}", sprite, image),
+        }
+
+        SvgError(path: PathBuf, msg: String) {
+            description("cannot rasterize SVG"),
+            display("cannot rasterize SVG '{}': {}", path.display(), msg),
+        }
+
         // `sdl2` crate has sooooo many ways to handle errors, it is comic as
         // this point
         SdlUnknownError(msg: String) {
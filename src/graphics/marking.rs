@@ -0,0 +1,124 @@
+use std::f32::consts::PI;
+
+use sdl2::pixels::Color;
+use sdl2::rect::{Point, Rect};
+use sdl2::render::Canvas;
+use sdl2::video::Window;
+
+use crate::game::RobotId;
+
+use super::error::*;
+
+
+// Thickness of shapes, relative to their size.
+const THICKNESS: f32 = 0.12f32;
+
+
+/// Outline of a shape unique to each robot, so they can be told apart
+/// without relying on colors.
+enum Shape {
+    Circle,
+    Triangle,
+    Square,
+    Cross,
+}
+
+
+impl Shape {
+    fn of(robot: RobotId) -> Shape {
+        match robot {
+            RobotId::Red => Shape::Circle,
+            RobotId::Green => Shape::Triangle,
+            RobotId::Blue => Shape::Square,
+            RobotId::Yellow => Shape::Cross,
+        }
+    }
+
+    // Lines to draw for a shape of `radius` centered on `center`.
+    fn strokes(&self, center: (f32, f32), radius: f32) -> Vec<Vec<Point>> {
+        let polygon = |corners: usize, start_angle: f32| {
+            (0..=corners)
+                .map(|i| {
+                    let angle = start_angle + 2f32 * PI * i as f32 / corners as f32;
+                    Point::new(
+                        (center.0 + radius * angle.cos()).round() as i32,
+                        (center.1 + radius * angle.sin()).round() as i32)
+                })
+                .collect::<Vec<_>>()
+        };
+        let point = |dx: f32, dy: f32| Point::new(
+            (center.0 + dx * radius).round() as i32,
+            (center.1 + dy * radius).round() as i32);
+
+        match self {
+            Shape::Circle => vec![polygon(24, 0f32)],
+            Shape::Triangle => vec![polygon(3, -PI / 2f32)],
+            Shape::Square => vec![polygon(4, PI / 4f32)],
+            Shape::Cross => vec![
+                vec![point(-0.7, -0.7), point(0.7, 0.7)],
+                vec![point(-0.7, 0.7), point(0.7, -0.7)],
+            ],
+        }
+    }
+}
+
+
+/// Draw the marking of `robot` in the middle of `area`, outlined in black to
+/// stay visible on any background.
+pub fn paint_marking(
+    canvas: &mut Canvas<Window>,
+    robot: RobotId,
+    area: Rect,
+    color: Color,
+    ) -> Result<()> {
+    let center = (area.center().x() as f32, area.center().y() as f32);
+    let radius = area.width().min(area.height()) as f32 * 0.5f32;
+    let half_thickness = (radius * THICKNESS).ceil() as i32;
+
+    // Stroke centered on the shape, one pixel narrower than its outline.
+    let strokes = Shape::of(robot).strokes(center, radius - half_thickness as f32 - 1f32);
+    let layers = [
+        (Color::RGB(0, 0, 0), half_thickness + 1),
+        (color, half_thickness),
+    ];
+
+    for &(layer_color, half_width) in layers.iter() {
+        canvas.set_draw_color(layer_color);
+        for dy in -half_width..=half_width {
+            for dx in -half_width..=half_width {
+                for stroke in strokes.iter() {
+                    let moved: Vec<Point> = stroke.iter()
+                        .map(|point| point.offset(dx, dy))
+                        .collect();
+                    canvas.draw_lines(moved.as_slice()).into_sdl_error()?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+
+/// Fill a disc in the middle of `area` with `color`, outlined in black, for
+/// the color of a robot to show whatever its sprite.
+pub fn paint_badge(
+    canvas: &mut Canvas<Window>,
+    area: Rect,
+    color: Color,
+    ) -> Result<()> {
+    let center = area.center();
+    let radius = area.width().min(area.height()) as i32 / 2;
+    let outline = ((radius as f32 * THICKNESS).ceil() as i32).max(1);
+
+    for &(layer_color, layer_radius) in [(Color::RGB(0, 0, 0), radius),
+                                         (color, radius - outline)].iter() {
+        canvas.set_draw_color(layer_color);
+        for dy in -layer_radius..=layer_radius {
+            let dx = ((layer_radius * layer_radius - dy * dy) as f32).sqrt().round() as i32;
+            canvas.draw_line(center.offset(-dx, dy), center.offset(dx, dy)).into_sdl_error()?;
+        }
+    }
+
+    Ok(())
+}
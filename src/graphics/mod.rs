@@ -1,10 +1,15 @@
 pub mod error;
 
+mod atlas;
+#[cfg(feature = "svg")]
+mod svg;
 mod texture;
 mod renderer;
 mod render;
 mod sprite;
 mod draw;
+mod marking;
+mod debug;
 
 pub use self::renderer::Renderer;
 pub use self::draw::DrawContext;
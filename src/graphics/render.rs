@@ -1,5 +1,12 @@
+use std::collections::HashMap;
+
 use sdl2::pixels::Color;
 
+use crate::config::{ThemeConfig, AccessibilityConfig};
+use crate::game::RobotId;
+
+use crate::palette::Palette;
+
 
 #[allow(dead_code)]
 pub enum AspectRatio {
@@ -14,14 +21,50 @@ pub enum AspectRatio {
 pub struct RenderSettings {
     pub background_color: Color,
     pub draw_walls_on_edge: bool,
+    pub robot_colors: HashMap<RobotId, Color>,
+    pub hud_color: Color,
+    pub markings: bool,
+    pub palette: Palette,
 }
 
 
 impl RenderSettings {
     pub fn new() -> RenderSettings {
+        RenderSettings::from_theme(&ThemeConfig::default(), &AccessibilityConfig::default())
+    }
+
+    pub fn from_theme(theme: &ThemeConfig, accessibility: &AccessibilityConfig)
+        -> RenderSettings {
+        let color = |[r, g, b]: [u8; 3]| Color::RGB(r, g, b);
+
         RenderSettings {
-            background_color: Color::RGB(220, 10, 10),
-            draw_walls_on_edge: false,
+            background_color: color(theme.background_color),
+            draw_walls_on_edge: theme.draw_walls_on_edge,
+            robot_colors: theme.robot_colors.iter()
+                .map(|(robot, rgb)| (*robot, color(*rgb)))
+                .collect(),
+            hud_color: color(theme.hud_color),
+            markings: accessibility.markings,
+            palette: accessibility.palette,
         }
     }
+
+    // Color of markings and of the target of `robot`.
+    pub fn marking_color(&self, robot: RobotId) -> Color {
+        let [r, g, b] = self.palette.color_of(robot);
+        Color::RGB(r, g, b)
+    }
+
+    // Whether robots wear a badge of their palette color, sprites being too
+    // saturated for a tint to change their hue.
+    pub fn has_badges(&self) -> bool {
+        self.palette != Palette::Standard
+    }
+
+    // Tint of the sprite of `robot`.
+    pub fn robot_color(&self, robot: RobotId) -> Color {
+        self.robot_colors.get(&robot)
+            .cloned()
+            .unwrap_or_else(|| Color::RGB(255, 255, 255))
+    }
 }
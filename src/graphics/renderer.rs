@@ -1,70 +1,275 @@
 use sdl2::rect::Rect;
+use sdl2::event::WindowEvent;
+use sdl2::pixels::Color;
+use sdl2::render::{Canvas, BlendMode};
+use sdl2::video::Window;
 
 use crate::positionning::{LogicalPos, RotateAngle, FlipAxis};
 use crate::moves::MovePossibility;
-use crate::board::EditableBoard;
-use crate::game::World;
+use crate::board::{EditableBoard, Border};
+use crate::game::{World, Target, Reachability};
+use crate::config::{AppConfig, ThemeConfig};
 
 use super::error::*;
 use super::draw::DrawContext;
 use super::sprite::SpriteId;
 use super::render::{RenderSettings, AspectRatio};
+use super::marking::{paint_marking, paint_badge};
+use super::debug::paint_cell_data;
+
+
+// Space around the board, in window coordinates.
+const BOARD_MARGIN: f32 = 10f32;
+// Thickness of the frame around the target cell, relative to the cell.
+const TARGET_FRAME: f32 = 0.08f32;
+// Size of markings, relative to the cell.
+const MARKING_SIZE: f32 = 0.4f32;
+// Size of the disc of palette color on robots, around their marking.
+const BADGE_SIZE: f32 = 0.55f32;
+// Opacity of the heatmap over reachable cells, and over the other ones.
+const HEATMAP_ALPHA: u8 = 110;
+const UNREACHABLE_ALPHA: u8 = 170;
+// Size of the hovered cell shown by the debug overlay, in cells.
+const READOUT_SCALE: u32 = 3;
+const READOUT_BACKGROUND: Color = Color { r: 30, g: 30, b: 30, a: 255 };
 
 
 pub struct Renderer<'r> {
     settings: RenderSettings,
     draw_ctx: DrawContext<'r, 'r>,
+    // Area the board texture was last built for, in pixels.
+    board_area: Option<Rect>,
+    // Where the board was last drawn, in pixels.
+    board_rect: Option<Rect>,
+    heatmap: Option<Reachability>,
+    debug_overlay: bool,
+    hovered: Option<LogicalPos>,
 }
 
 
 impl<'r> Renderer<'r> {
     pub fn new(draw_ctx: DrawContext<'r, 'r>) -> Renderer<'r> {
         let settings = RenderSettings::new();
-        Renderer { settings, draw_ctx, }
+        Renderer {
+            settings,
+            draw_ctx,
+            board_area: None,
+            board_rect: None,
+            heatmap: None,
+            debug_overlay: false,
+            hovered: None,
+        }
+    }
+
+
+    /// Color cells by the moves a robot needs to reach them, over the board.
+    pub fn set_heatmap(&mut self, heatmap: Option<Reachability>) {
+        self.heatmap = heatmap;
+    }
+
+
+    pub fn heatmap(&self) -> Option<&Reachability> {
+        self.heatmap.as_ref()
+    }
+
+
+    pub fn debug_overlay(&self) -> bool {
+        self.debug_overlay
+    }
+
+
+    /// Draw the data of every cell, as the board gives it, over the board.
+    pub fn set_debug_overlay(&mut self, enabled: bool) {
+        self.debug_overlay = enabled;
+    }
+
+
+    /// Cell to highlight, under the mouse.
+    pub fn set_hovered(&mut self, pos: Option<LogicalPos>) {
+        self.hovered = pos;
+    }
+
+
+    /// Cell of `board` at (x, y) in window coordinates, as last drawn.
+    pub fn cell_at(&self, board: &dyn EditableBoard, x: i32, y: i32) -> Option<LogicalPos> {
+        let board_rect = self.board_rect?;
+        let ratio = self.pixel_ratio();
+        let px = x as f32 * ratio - board_rect.x() as f32;
+        let py = y as f32 * ratio - board_rect.y() as f32;
+        if px < 0f32 || py < 0f32 {
+            return None;
+        }
+
+        let dimensions = board.dimensions();
+        let pos = LogicalPos {
+            x: (px * dimensions.width as f32 / board_rect.width() as f32) as usize,
+            y: (py * dimensions.height as f32 / board_rect.height() as f32) as usize,
+        };
+        if dimensions.contains(&pos) { Some(pos) } else { None }
     }
 
 
-    pub fn render(&mut self, board: &EditableBoard, world: &World) -> Result<()> {
+    /**
+     * Use the colors and sprites of `theme`, the ones of `config` being used
+     * when the theme has no atlas. The current theme is kept when sprites of
+     * the new one cannot be loaded.
+     */
+    pub fn set_theme(&mut self, config: &AppConfig, theme: &ThemeConfig) -> Result<()> {
+        let mut tm = self.draw_ctx.tm.borrow().empty();
+        match theme.atlas_path.as_ref().or(config.atlas_path.as_ref()) {
+            Some(atlas_path) => tm.load_atlas(atlas_path)?,
+            None => tm.load_static(&config.assets_path)?,
+        }
+        *self.draw_ctx.tm.borrow_mut() = tm;
+
+        self.settings = RenderSettings::from_theme(theme, &config.accessibility);
+        self.invalidate_board();
+        Ok(())
+    }
+
+
+    // Shown by the window manager, to report what cannot be drawn.
+    pub fn set_title(&mut self, title: &str) -> Result<()> {
+        self.draw_ctx.canvas.window_mut()
+            .set_title(title)
+            .map_err(|e| e.to_string())
+            .into_sdl_error()
+    }
+
+
+    pub fn render(
+        &mut self,
+        board: &dyn EditableBoard,
+        world: &World,
+        target: Option<&Target>,
+        ) -> Result<()> {
         self.prepare(board)?;
-        self.render_all(board, world)?;
+        self.render_all(board, world, target)?;
         self.draw_ctx.canvas.present();
         Ok(())
     }
 
 
-    fn prepare(&mut self, board: &EditableBoard) -> Result<()> {
+    fn prepare(&mut self, board: &dyn EditableBoard) -> Result<()> {
         self.draw_ctx.canvas.set_draw_color(self.settings.background_color);
         self.draw_ctx.canvas.clear();
 
-        // Initialise the first time only - or when board changes
+        // Initialise the first time only - or when board or window changes
         if !self.draw_ctx.tm.borrow().sprite_exists(&SpriteId::CurrentBoard) {
-            self.init_board(board)?;
+            let area = self.board_area()?;
+            self.init_board(board, area)?;
+            self.board_area = Some(area);
         }
 
         Ok(())
     }
 
+    /**
+     * Keep the board texture at its on-screen size, so walls stay crisp.
+     * Moving the window to another display may change its pixel density.
+     */
+    pub fn handle_window_event(&mut self, event: &WindowEvent) -> Result<()> {
+        match event {
+            WindowEvent::Resized(..)
+            | WindowEvent::SizeChanged(..)
+            | WindowEvent::Moved(..)
+            | WindowEvent::Maximized
+            | WindowEvent::Restored if self.board_area != Some(self.board_area()?) => {
+                self.invalidate_board();
+            },
+            _ => {},
+        }
+
+        Ok(())
+    }
+
+    // Pixels per window coordinate, above 1 on HiDPI displays.
+    fn pixel_ratio(&self) -> f32 {
+        let window = self.draw_ctx.canvas.window();
+        let (width, _) = window.size();
+        let (drawable_width, _) = window.drawable_size();
+
+        if width == 0 { 1f32 }
+        else { drawable_width as f32 / width as f32 }
+    }
+
+    fn board_area(&self) -> Result<Rect> {
+        let (width, height) = self.draw_ctx.canvas.output_size()?;
+        let margin = (BOARD_MARGIN * self.pixel_ratio()).round() as u32;
+
+        Ok(Rect::new(
+            margin as i32,
+            margin as i32,
+            width.saturating_sub(2 * margin).max(1),
+            height.saturating_sub(2 * margin).max(1)))
+    }
+
     pub fn invalidate_board(&mut self) {
         self.draw_ctx.tm.borrow_mut()
             .remove_sprite(&SpriteId::CurrentBoard);
+        // Computed on the previous board.
+        self.heatmap = None;
     }
 
     /**
      * Render all game items.
      */
-    pub fn render_all(&mut self, board: &EditableBoard, world: &World) -> Result<()>
+    pub fn render_all(
+        &mut self,
+        board: &dyn EditableBoard,
+        world: &World,
+        target: Option<&Target>,
+        ) -> Result<()>
     {
-        let (width, height) = self.draw_ctx.canvas.output_size()?;
-        let geom = Rect::new(10, 10, width - 20, height - 20);
+        let geom = self.board_area()?;
 
         // First, draw background
-        let board_rect = self.paint_sprite(
-            &SpriteId::CurrentBoard,
-            geom,
-            AspectRatio::KeepIn)?;
+        let board_rect = match (world.quadrants.as_ref(), world.board_alpha) {
+            (Some(_), _) => self.paint_quadrants(geom, world)?,
+            (None, Some(alpha)) => {
+                let id = SpriteId::CurrentBoard;
+                let board_rect = self.fit_sprite(&id, geom, AspectRatio::KeepIn)?;
+                let alpha = (alpha.clamp(0f32, 1f32) * 255f32).round() as u8;
+                self.draw_ctx.draw_tinted(&id, board_rect, Color::RGB(255, 255, 255), alpha)?;
+                board_rect
+            },
+            (None, None) => self.paint_sprite(
+                &SpriteId::CurrentBoard,
+                geom,
+                AspectRatio::KeepIn)?,
+        };
 
-        let side = board.side_length().0;
-        let side_f = side as f32;
+        self.board_rect = Some(board_rect);
+
+        let dimensions = board.dimensions();
+        let columns = dimensions.width as f32;
+        let rows = dimensions.height as f32;
+
+        // Then, overlays, once the board is in place
+        if let (Some(heatmap), false) = (self.heatmap.as_ref(), world.is_board_moving()) {
+            Self::paint_heatmap(self.draw_ctx.canvas, heatmap, board_rect)?;
+        }
+        if self.debug_overlay && !world.is_board_moving() {
+            self.paint_debug_overlay(board, board_rect)?;
+        }
+
+        // Then, the target
+        if let (Some(target), false) = (target, world.is_board_moving()) {
+            let cell_rect = Self::cell_rect(
+                board_rect, columns, rows, target.pos.x as f32, target.pos.y as f32);
+            self.paint_frame(cell_rect)?;
+
+            // Tell which robot has to reach it.
+            let color = self.settings.marking_color(target.robot);
+            let inner = Self::shrink(cell_rect, 0.5f32);
+            if self.settings.markings {
+                paint_marking(self.draw_ctx.canvas, target.robot, inner, color)?;
+            }
+            else {
+                self.draw_ctx.canvas.set_draw_color(color);
+                self.draw_ctx.canvas.fill_rect(inner).into_sdl_error()?;
+            }
+        }
 
         // Then, draw robots
         for robot in world.robots.iter() {
@@ -73,32 +278,247 @@ impl<'r> Renderer<'r> {
                 None => continue
             };
 
-            let x = pos.x * board_rect.width() as f32 / side_f;
-            let y = pos.y * board_rect.height() as f32 / side_f;
+            let cell_rect = Self::cell_rect(board_rect, columns, rows, pos.x, pos.y);
 
-            let screen_rect = Rect::new(
-                board_rect.x() + x.floor() as i32,
-                board_rect.y() + y.floor() as i32,
-                (board_rect.width() as f32 / side_f).floor() as u32,
-                (board_rect.height() as f32 / side_f).floor() as u32,
+            let screen_rect = Rect::from_center(
+                cell_rect.center(),
+                (cell_rect.width() as f32 * robot.scale.x).floor() as u32,
+                (cell_rect.height() as f32 * robot.scale.y).floor() as u32,
                 );
 
-            let _ = self.paint_sprite(
-                &SpriteId::Robot(robot.id.clone()),
-                screen_rect,
-                AspectRatio::Stretch)?;
+            let id = SpriteId::Robot(robot.id);
+            if robot.alpha > 0f32 {
+                let alpha = (robot.alpha.min(1f32) * 255f32).round() as u8;
+                let color = self.settings.robot_color(robot.id);
+                self.draw_ctx.draw_tinted(&id, screen_rect, color, alpha)?;
+            }
+            if self.settings.has_badges() && robot.alpha >= 1f32 {
+                let color = self.settings.marking_color(robot.id);
+                let area = Self::shrink(screen_rect, BADGE_SIZE);
+                paint_badge(self.draw_ctx.canvas, area, color)?;
+            }
+            if self.settings.markings && robot.alpha >= 1f32 {
+                let color = self.settings.marking_color(robot.id);
+                let area = Self::shrink(screen_rect, MARKING_SIZE);
+                paint_marking(self.draw_ctx.canvas, robot.id, area, color)?;
+            }
+        }
+
+        Ok(())
+    }
+
+
+    // Screen area of the cell at (x, y), which may be between cells.
+    fn cell_rect(board_rect: Rect, columns: f32, rows: f32, x: f32, y: f32) -> Rect {
+        let px = x * board_rect.width() as f32 / columns;
+        let py = y * board_rect.height() as f32 / rows;
+
+        Rect::new(
+            board_rect.x() + px.floor() as i32,
+            board_rect.y() + py.floor() as i32,
+            (board_rect.width() as f32 / columns).floor() as u32,
+            (board_rect.height() as f32 / rows).floor() as u32,
+            )
+    }
+
+
+    // Part of `area` around its center, `ratio` times as large.
+    fn shrink(area: Rect, ratio: f32) -> Rect {
+        Rect::from_center(
+            area.center(),
+            ((area.width() as f32 * ratio).round() as u32).max(1),
+            ((area.height() as f32 * ratio).round() as u32).max(1))
+    }
+
+
+    // From green for the closest cells to red for the farthest ones, cells
+    // out of reach being darkened.
+    fn paint_heatmap(
+        canvas: &mut Canvas<Window>,
+        heatmap: &Reachability,
+        board_rect: Rect,
+        ) -> Result<()> {
+        let dimensions = heatmap.dimensions();
+        let columns = dimensions.width as f32;
+        let rows = dimensions.height as f32;
+        let max_moves = heatmap.max_moves().max(1) as f32;
+
+        let blend_mode = canvas.blend_mode();
+        canvas.set_blend_mode(BlendMode::Blend);
+
+        for y in 0..dimensions.height {
+            for x in 0..dimensions.width {
+                let color = match heatmap.moves_to(&LogicalPos { x, y }) {
+                    Some(moves) => {
+                        let t = moves as f32 / max_moves;
+                        let red = (255f32 * (2f32 * t).min(1f32)).round() as u8;
+                        let green = (255f32 * (2f32 * (1f32 - t)).min(1f32)).round() as u8;
+                        Color::RGBA(red, green, 0, HEATMAP_ALPHA)
+                    },
+                    None => Color::RGBA(0, 0, 0, UNREACHABLE_ALPHA),
+                };
+
+                let cell_rect = Self::cell_rect(board_rect, columns, rows, x as f32, y as f32);
+                canvas.set_draw_color(color);
+                canvas.fill_rect(cell_rect).into_sdl_error()?;
+            }
+        }
+
+        canvas.set_blend_mode(blend_mode);
+        Ok(())
+    }
+
+
+    // Cell data straight from the board, not from its cached texture, and
+    // the hovered cell.
+    fn paint_debug_overlay(&mut self, board: &dyn EditableBoard, board_rect: Rect) -> Result<()> {
+        let dimensions = board.dimensions();
+        let columns = dimensions.width as f32;
+        let rows = dimensions.height as f32;
+
+        for y in 0..dimensions.height {
+            for x in 0..dimensions.width {
+                let pos = LogicalPos { x, y };
+                if let Ok(moves) = board.moves_from(&pos) {
+                    let cell_rect = Self::cell_rect(
+                        board_rect, columns, rows, x as f32, y as f32);
+                    paint_cell_data(self.draw_ctx.canvas, &pos, &moves, cell_rect)?;
+                }
+            }
+        }
+
+        if let Some(pos) = self.hovered {
+            let cell_rect = Self::cell_rect(
+                board_rect, columns, rows, pos.x as f32, pos.y as f32);
+            self.paint_frame(cell_rect)?;
+
+            // A cell the board cannot tell about has nothing to show.
+            if let Ok(moves) = board.moves_from(&pos) {
+                let area = self.readout_area(board_rect, cell_rect)?;
+                self.draw_ctx.canvas.set_draw_color(READOUT_BACKGROUND);
+                self.draw_ctx.canvas.fill_rect(area).into_sdl_error()?;
+                paint_cell_data(self.draw_ctx.canvas, &pos, &moves, area)?;
+            }
+        }
+
+        Ok(())
+    }
+
+
+    // Where to show the hovered cell enlarged: beside the board when the
+    // window leaves room for it, otherwise over the board corner farthest
+    // from the cell.
+    fn readout_area(&self, board_rect: Rect, cell_rect: Rect) -> Result<Rect> {
+        let (width, _) = self.draw_ctx.canvas.output_size().into_sdl_error()?;
+        let side = cell_rect.width().max(cell_rect.height()) * READOUT_SCALE;
+        let gap = (BOARD_MARGIN * self.pixel_ratio()).round() as i32;
+
+        let x = if board_rect.right() + gap + side as i32 <= width as i32 {
+            board_rect.right() + gap
+        }
+        else if board_rect.left() - gap - side as i32 >= 0 {
+            board_rect.left() - gap - side as i32
+        }
+        else if cell_rect.center().x() < board_rect.center().x() {
+            board_rect.right() - side as i32
+        }
+        else {
+            board_rect.left()
+        };
+
+        Ok(Rect::new(x, board_rect.top(), side, side))
+    }
+
+
+    // Outline `area` from the inside, in the HUD color.
+    fn paint_frame(&mut self, area: Rect) -> Result<()> {
+        let thickness = (area.width().min(area.height()) as f32 * TARGET_FRAME)
+            .ceil() as u32;
+        let canvas = &mut self.draw_ctx.canvas;
+        canvas.set_draw_color(self.settings.hud_color);
+
+        for i in 0..thickness {
+            if area.width() <= 2 * i || area.height() <= 2 * i {
+                break;
+            }
+            canvas
+                .draw_rect(Rect::new(
+                    area.x() + i as i32,
+                    area.y() + i as i32,
+                    area.width() - 2 * i,
+                    area.height() - 2 * i))
+                .into_sdl_error()?;
         }
 
         Ok(())
     }
 
 
+    // Draw the board cut in four, each part moved as told by the world.
+    fn paint_quadrants(&mut self, area: Rect, world: &World) -> Result<Rect> {
+        let id = SpriteId::CurrentBoard;
+        let board_rect = self.fit_sprite(&id, area, AspectRatio::KeepIn)?;
+        let sprite_geom = self.draw_ctx.tm.borrow().get_sprite(&id)?.geom;
+
+        let quadrants = match world.quadrants {
+            Some(ref quadrants) => quadrants,
+            None => return Ok(board_rect),
+        };
+
+        for border in Border::all() {
+            let quadrant = match quadrants.get(border.index()) {
+                Some(quadrant) => quadrant,
+                None => continue,
+            };
+
+            let outward = border.outward();
+            let part = Self::half_of(
+                Rect::new(0, 0, sprite_geom.width(), sprite_geom.height()),
+                outward.x > 0f32, outward.y > 0f32);
+            let mut dest = Self::half_of(board_rect, outward.x > 0f32, outward.y > 0f32);
+
+            dest.offset(
+                (quadrant.offset.x * board_rect.width() as f32).round() as i32,
+                (quadrant.offset.y * board_rect.height() as f32).round() as i32);
+
+            self.draw_ctx.draw_part(&id, part, dest, quadrant.angle as f64)?;
+        }
+
+        Ok(board_rect)
+    }
+
+
+    // One quarter of `rect`, on the right and/or bottom side.
+    fn half_of(rect: Rect, right: bool, bottom: bool) -> Rect {
+        let left_width = rect.width() / 2;
+        let top_height = rect.height() / 2;
+
+        Rect::new(
+            rect.x() + if right { left_width as i32 } else { 0 },
+            rect.y() + if bottom { top_height as i32 } else { 0 },
+            if right { rect.width() - left_width } else { left_width },
+            if bottom { rect.height() - top_height } else { top_height })
+    }
+
+
     fn paint_sprite(
         &mut self,
         id: &SpriteId,
         area: Rect,
         aspect: AspectRatio
         ) -> Result<Rect> {
+        let display_geom = self.fit_sprite(id, area, aspect)?;
+        self.draw_ctx.draw(id, display_geom)?;
+        Ok(display_geom)
+    }
+
+
+    fn fit_sprite(
+        &self,
+        id: &SpriteId,
+        area: Rect,
+        aspect: AspectRatio
+        ) -> Result<Rect> {
         let display_geom = match aspect {
             AspectRatio::Stretch => area,
             AspectRatio::KeepIn => {
@@ -119,22 +539,34 @@ impl<'r> Renderer<'r> {
             },
         };
 
-        self.draw_ctx.draw(id, display_geom)?;
-
         Ok(display_geom)
     }
 
 
-    fn init_board(&mut self, board: &EditableBoard) -> Result<()> {
+    fn init_board(&mut self, board: &dyn EditableBoard, area: Rect) -> Result<()> {
         let (format, width, height);
 
         {
-            let side = board.side_length().0 as u32;
+            let dimensions = board.dimensions();
+            let columns = dimensions.width.max(1) as u32;
+            let rows = dimensions.height.max(1) as u32;
+
+            // The texture is as large as the board will be displayed.
+            let cell = self.draw_ctx.tm.borrow().get_sprite(&SpriteId::CellBackground)?.geom;
+            let board_width = (cell.width() * columns) as f32;
+            let board_height = (cell.height() * rows) as f32;
+            let scale = (area.width() as f32 / board_width)
+                .min(area.height() as f32 / board_height);
+            width = ((board_width * scale).floor() as u32).max(1);
+            height = ((board_height * scale).floor() as u32).max(1);
+
+            // Sharper cells when they are drawn from vector images.
+            self.draw_ctx.tm.borrow_mut()
+                .rasterize_for(&SpriteId::CellBackground, (width / columns).max(1));
+
             let tm = self.draw_ctx.tm.borrow();
             let board_cell = tm.get_sprite(&SpriteId::CellBackground)?;
             format = tm.get_texture(board_cell)?.query().format;
-            width = board_cell.geom.width() * side;
-            height = board_cell.geom.height() * side;
         }
 
         let draw_walls_on_edge = self.settings.draw_walls_on_edge;
@@ -152,12 +584,13 @@ impl<'r> Renderer<'r> {
 
     fn draw_board<'c, 't>(
         draw_ctx: &mut DrawContext<'c, 't>,
-        board: &EditableBoard,
+        board: &dyn EditableBoard,
         draw_walls_on_edge: bool
         ) -> Result<()>
         {
-            let side = board.side_length().0;
-            let side_f = side as f32;
+            let dimensions = board.dimensions();
+            let columns = dimensions.width as f32;
+            let rows = dimensions.height as f32;
 
             let (width, height) = draw_ctx.canvas
                 .output_size()
@@ -166,13 +599,13 @@ impl<'r> Renderer<'r> {
             let width = width as f32;
             let height = height as f32;
 
-            for y in 0..side {
-                for x in 0..side {
-                    let px = ((x as f32 / side_f) * width).floor();
-                    let py = ((y as f32 / side_f) * height).floor();
+            for y in 0..dimensions.height {
+                for x in 0..dimensions.width {
+                    let px = ((x as f32 / columns) * width).floor();
+                    let py = ((y as f32 / rows) * height).floor();
 
-                    let next_x = (((x as f32 + 1f32) / side_f) * width).floor();
-                    let next_y = (((y as f32 + 1f32) / side_f) * height).floor();
+                    let next_x = (((x as f32 + 1f32) / columns) * width).floor();
+                    let next_y = (((y as f32 + 1f32) / rows) * height).floor();
 
                     let geom = Rect::new(
                         px as i32,
@@ -194,9 +627,9 @@ impl<'r> Renderer<'r> {
                     // move through it.
                     if !draw_walls_on_edge {
                         moves.up |= y == 0;
-                        moves.down |= y + 1 == side;
+                        moves.down |= y + 1 == dimensions.height;
                         moves.left |= x == 0;
-                        moves.right |= x + 1 == side;
+                        moves.right |= x + 1 == dimensions.width;
                     }
 
 
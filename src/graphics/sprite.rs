@@ -14,11 +14,59 @@ pub enum SpriteId {
     CurrentBoard,
     //
     Robot(RobotId),
+    // Any other sprite of an atlas, not known by the game itself.
+    Named(String),
+}
+
+
+impl SpriteId {
+    // Name of the sprite in atlas files.
+    pub fn from_name(name: &str) -> SpriteId {
+        match name {
+            "cell-background" => SpriteId::CellBackground,
+            "forbidden-cell" => SpriteId::ForbiddenCell,
+            "corner-wall" => SpriteId::CornerWall,
+            "side-wall" => SpriteId::SideWall,
+            "robot-red" => SpriteId::Robot(RobotId::Red),
+            "robot-green" => SpriteId::Robot(RobotId::Green),
+            "robot-blue" => SpriteId::Robot(RobotId::Blue),
+            "robot-yellow" => SpriteId::Robot(RobotId::Yellow),
+            other => SpriteId::Named(other.to_string()),
+        }
+    }
 }
 
 
 #[derive(Clone)]
 pub struct Sprite {
     pub texture_index: usize,
-    pub geom: Rect
+    pub geom: Rect,
+    pub pivot: (f32, f32),
+    pub scale: f32,
+}
+
+
+impl Sprite {
+    pub fn new(texture_index: usize, geom: Rect) -> Sprite {
+        Sprite { texture_index, geom, pivot: (0.5, 0.5), scale: 1.0 }
+    }
+
+    // Where to draw the sprite to fill `area`, taking its scale into
+    // account: the pivot stays at the same relative place within `area`.
+    pub fn place(&self, area: Rect) -> Rect {
+        if self.scale == 1.0 {
+            return area;
+        }
+
+        let width = area.width() as f32 * self.scale;
+        let height = area.height() as f32 * self.scale;
+        let pivot_x = area.x() as f32 + area.width() as f32 * self.pivot.0;
+        let pivot_y = area.y() as f32 + area.height() as f32 * self.pivot.1;
+
+        Rect::new(
+            (pivot_x - width * self.pivot.0).round() as i32,
+            (pivot_y - height * self.pivot.1).round() as i32,
+            width.round() as u32,
+            height.round() as u32)
+    }
 }
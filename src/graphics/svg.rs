@@ -0,0 +1,34 @@
+use std::fs;
+use std::path::Path;
+
+use resvg::{tiny_skia, usvg};
+
+use super::error::*;
+
+
+/// Render a SVG file stretched to `width` x `height` pixels, as RGBA bytes
+/// with straight (not premultiplied) alpha.
+pub fn rasterize(path: &Path, width: u32, height: u32) -> Result<Vec<u8>> {
+    let svg_error = |msg: String| ErrorKind::SvgError(path.to_path_buf(), msg);
+
+    let data = fs::read(path)?;
+    let tree = usvg::Tree::from_data(&data, &usvg::Options::default())
+        .map_err(|e| svg_error(e.to_string()))?;
+
+    let mut pixmap = tiny_skia::Pixmap::new(width, height)
+        .ok_or_else(|| svg_error(format!("invalid size {}x{}", width, height)))?;
+
+    let size = tree.size();
+    let transform = tiny_skia::Transform::from_scale(
+        width as f32 / size.width(),
+        height as f32 / size.height());
+    resvg::render(&tree, transform, &mut pixmap.as_mut());
+
+    let mut pixels = Vec::with_capacity(pixmap.data().len());
+    for pixel in pixmap.pixels() {
+        let color = pixel.demultiply();
+        pixels.extend_from_slice(&[color.red(), color.green(), color.blue(), color.alpha()]);
+    }
+
+    Ok(pixels)
+}
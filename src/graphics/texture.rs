@@ -1,9 +1,11 @@
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use sdl2::rect::Rect;
 use sdl2::surface::Surface;
 use sdl2::render::{Texture, TextureCreator};
+#[cfg(feature = "svg")]
+use sdl2::render::BlendMode;
 use sdl2::video::WindowContext;
 use sdl2::image::LoadSurface;
 use sdl2::pixels::PixelFormatEnum;
@@ -12,6 +14,29 @@ use crate::game::RobotId;
 
 use super::error::*;
 use super::sprite::{Sprite, SpriteId};
+use super::atlas::AtlasDescriptor;
+#[cfg(feature = "svg")]
+use super::svg;
+
+
+// Image loaded from an atlas, that may be rasterized again from a SVG.
+#[cfg_attr(not(feature = "svg"), allow(dead_code))]
+struct AtlasImage {
+    texture_index: usize,
+    // Size of the raster image, sprites rectangles being relative to it.
+    size: (u32, u32),
+    svg: Option<PathBuf>,
+    // Size of the current texture, relative to `size`.
+    scale: f32,
+}
+
+
+#[cfg_attr(not(feature = "svg"), allow(dead_code))]
+struct AtlasSprite {
+    id: SpriteId,
+    image: usize,
+    geom: Rect,
+}
 
 
 pub struct TextureManager<'t> {
@@ -19,6 +44,8 @@ pub struct TextureManager<'t> {
     surfaces: Vec<Surface<'t>>,
     textures: Vec<Option<Texture<'t>>>,
     sprites: HashMap<SpriteId, Sprite>,
+    atlas_images: Vec<AtlasImage>,
+    atlas_sprites: Vec<AtlasSprite>,
 }
 
 
@@ -31,9 +58,17 @@ impl<'t> TextureManager<'t> {
             surfaces: Vec::new(),
             textures: Vec::new(),
             sprites: HashMap::new(),
+            atlas_images: Vec::new(),
+            atlas_sprites: Vec::new(),
         }
     }
 
+    /// Manager creating textures for the same window, with nothing loaded
+    /// yet, to load other sprites without losing the current ones.
+    pub fn empty(&self) -> TextureManager<'t> {
+        TextureManager::new(self.creator)
+    }
+
     pub fn load_static(&mut self, path: &PathBuf) -> Result<()> {
         self.surfaces = vec![
             Surface::from_file(path).into_sdl_error()?
@@ -60,16 +95,124 @@ impl<'t> TextureManager<'t> {
         ];
 
         for (i, id) in ids.into_iter().enumerate() {
-            let sprite = Sprite {
-                texture_index: 0,
-                geom: Rect::new(i as i32 * side as i32, 0, side, side)
-            };
+            let sprite = Sprite::new(
+                0,
+                Rect::new(i as i32 * side as i32, 0, side, side));
             self.sprites.insert(id, sprite);
         }
 
         Ok(())
     }
 
+    /// Load sprites as described by an atlas file, instead of the fixed
+    /// layout of `load_static`.
+    pub fn load_atlas(&mut self, path: &Path) -> Result<()> {
+        let atlas = AtlasDescriptor::load(path)?;
+        let first_image = self.atlas_images.len();
+
+        // Raster images are always loaded, as a fallback for SVG ones.
+        for image in atlas.images.iter() {
+            let surface = Surface::from_file(image.path()).into_sdl_error()?;
+            let texture = self.creator
+                .create_texture_from_surface(&surface)
+                .map_err(|e| format!("{:?}", e))
+                .into_sdl_error()?;
+            let info = texture.query();
+
+            self.surfaces.push(surface);
+            let texture_index = self.add_texture(texture);
+
+            self.atlas_images.push(AtlasImage {
+                texture_index,
+                size: (info.width, info.height),
+                svg: image.svg().map(Path::to_path_buf),
+                scale: 1.0,
+            });
+        }
+
+        for (name, desc) in atlas.sprites.iter() {
+            let id = SpriteId::from_name(name);
+            let image = first_image + desc.image;
+            let geom = Rect::new(desc.rect.x, desc.rect.y, desc.rect.width, desc.rect.height);
+
+            let mut sprite = Sprite::new(self.atlas_images[image].texture_index, geom);
+            sprite.pivot = (desc.pivot[0], desc.pivot[1]);
+            sprite.scale = desc.scale;
+
+            self.sprites.insert(id.clone(), sprite);
+            self.atlas_sprites.push(AtlasSprite { id, image, geom });
+        }
+
+        Ok(())
+    }
+
+    /// Rasterize SVG images again, so that sprite `id` is `width` pixels
+    /// wide. Images that cannot be rasterized keep their current texture.
+    pub fn rasterize_for(&mut self, id: &SpriteId, width: u32) {
+        let reference = self.atlas_sprites.iter()
+            .find(|sprite| &sprite.id == id)
+            .map(|sprite| sprite.geom.width());
+
+        if let Some(reference) = reference {
+            self.rasterize(width as f32 / reference as f32);
+        }
+    }
+
+    #[cfg(feature = "svg")]
+    fn rasterize(&mut self, scale: f32) {
+        for image in self.atlas_images.iter_mut() {
+            let path = match image.svg {
+                Some(ref path) if image.scale != scale => path,
+                _ => continue,
+            };
+
+            let width = ((image.size.0 as f32 * scale).round() as u32).max(1);
+            let height = ((image.size.1 as f32 * scale).round() as u32).max(1);
+            let creator = self.creator;
+
+            let texture = svg::rasterize(path, width, height)
+                .and_then(|pixels| {
+                    let mut texture = creator
+                        // Bytes are in RGBA order, whatever the endianness.
+                        .create_texture_static(PixelFormatEnum::RGBA32, width, height)
+                        .into_sdl_error()?;
+                    texture
+                        .update(None, &pixels, width as usize * 4)
+                        .map_err(|e| format!("{}", e))
+                        .into_sdl_error()?;
+                    texture.set_blend_mode(BlendMode::Blend);
+                    Ok(texture)
+                });
+
+            match texture {
+                Ok(texture) => {
+                    self.textures[image.texture_index] = Some(texture);
+                    image.scale = scale;
+                },
+                Err(error) => {
+                    println!("Keep previous texture: {}", error);
+                },
+            }
+        }
+
+        for atlas_sprite in self.atlas_sprites.iter() {
+            let scale = self.atlas_images[atlas_sprite.image].scale;
+            let geom = atlas_sprite.geom;
+
+            if let Some(sprite) = self.sprites.get_mut(&atlas_sprite.id) {
+                sprite.geom = Rect::new(
+                    (geom.x() as f32 * scale).round() as i32,
+                    (geom.y() as f32 * scale).round() as i32,
+                    ((geom.width() as f32 * scale).round() as u32).max(1),
+                    ((geom.height() as f32 * scale).round() as u32).max(1));
+            }
+        }
+    }
+
+    #[cfg(not(feature = "svg"))]
+    fn rasterize(&mut self, _scale: f32) {
+    }
+
     // Texture management below
 
     pub fn add_texture(&mut self, texture: Texture<'t>) -> usize {
@@ -80,7 +223,14 @@ impl<'t> TextureManager<'t> {
     pub fn get_texture(&self, sprite: &Sprite) -> Result<&Texture<'t>> {
         match self.textures.get(sprite.texture_index) {
             Some(Some(ref texture)) => Ok(texture),
-            _ => bail!(ErrorKind::MissingTexture(sprite.texture_index.clone()))
+            _ => bail!(ErrorKind::MissingTexture(sprite.texture_index))
+        }
+    }
+
+    pub fn get_texture_mut(&mut self, sprite: &Sprite) -> Result<&mut Texture<'t>> {
+        match self.textures.get_mut(sprite.texture_index) {
+            Some(Some(ref mut texture)) => Ok(texture),
+            _ => bail!(ErrorKind::MissingTexture(sprite.texture_index))
         }
     }
 
@@ -116,7 +266,7 @@ impl<'t> TextureManager<'t> {
 
         let texture_index = self.add_texture(texture);
 
-        let sprite = Sprite { texture_index, geom };
+        let sprite = Sprite::new(texture_index, geom);
         self.sprites.insert(id, sprite.clone());
 
         sprite
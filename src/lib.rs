@@ -0,0 +1,31 @@
+//! Rules of the game, without SDL unless the `sdl` feature is on: boards and
+//! how they are built, robots and their moves, and a solver.
+
+// Application related
+pub mod error;
+pub mod config;
+pub mod cli;
+
+// Math & Abstract
+#[macro_use]
+pub mod positionning;
+pub mod moves;
+pub mod wall;
+pub mod palette;
+
+// Game & Entities related
+pub mod board;
+pub mod game;
+pub mod solver;
+pub mod engine;
+pub mod bench;
+pub mod opponent;
+
+// Network related
+pub mod net;
+
+// Draw related
+#[cfg(feature = "sdl")]
+pub mod graphics;
+#[cfg(feature = "terminal")]
+pub mod terminal;
@@ -1,41 +1,159 @@
-use std::time::{Duration, Instant};
 use std::rc::Rc;
+#[cfg(feature = "sdl")]
+use std::time::{Duration, Instant};
 
+#[cfg(feature = "sdl")]
 use sdl2::event::Event;
+#[cfg(feature = "sdl")]
 use sdl2::keyboard::Keycode;
+#[cfg(feature = "sdl")]
 use sdl2::image::{self, InitFlag};
+#[cfg(feature = "sdl")]
+use error_chain::ChainedError;
 
-// Application related
-mod error;
-mod config;
-
-// Math & Abstract
-#[macro_use]
-mod positionning;
-mod moves;
-mod wall;
+use rrr::{error, config, cli, board, net, engine, bench};
+#[cfg(feature = "sdl")]
+use rrr::{game, graphics, opponent};
+#[cfg(feature = "terminal")]
+use rrr::terminal;
 
-// Game & Entities related
-mod board;
-mod game;
 
-// Draw related
-mod graphics;
+#[cfg(feature = "sdl")]
+const WINDOW_TITLE: &str = "Ricochet robot";
+// Time between checks of the config file for changes.
+#[cfg(feature = "sdl")]
+const CONFIG_POLL_PERIOD: Duration = Duration::from_millis(500);
 
 
 fn main() -> error::Result<()> {
+    let mode = cli::parse_args(std::env::args().skip(1))?;
+
+    if let cli::Mode::ValidateTiles { config_path } = mode {
+        let config_path = config_path.unwrap_or_else(|| config::DEFAULT_PATH.to_string());
+        return validate_tiles(std::path::Path::new(&config_path));
+    }
+    if let cli::Mode::Engine = mode {
+        let stdin = std::io::BufReader::new(std::io::stdin());
+        engine::run_builtin(stdin, std::io::stdout())?;
+        return Ok(());
+    }
+
     let config = Rc::new(config::load_default()?);
 
+    match mode {
+        #[cfg(feature = "sdl")]
+        cli::Mode::Local => {
+            run_window(&config, None)
+        },
+        #[cfg(feature = "terminal")]
+        cli::Mode::Terminal => {
+            terminal::run(&config)
+        },
+        #[cfg(not(feature = "terminal"))]
+        cli::Mode::Terminal => {
+            error::bail!(error::ErrorKind::InvalidArguments(
+                    "built without the terminal feature".to_string()))
+        },
+        cli::Mode::Server { addr } => {
+            let mut server = net::Server::bind(addr.as_str(), &config)?;
+            server.run()?;
+            Ok(())
+        },
+        #[cfg(feature = "sdl")]
+        cli::Mode::Client { addr, name, role } => {
+            let client = net::Client::connect(addr.as_str(), &name, role)?;
+            run_window(&config, Some(client))
+        },
+        #[cfg(not(feature = "sdl"))]
+        cli::Mode::Local | cli::Mode::Client { .. } => {
+            error::bail!(error::ErrorKind::InvalidArguments(
+                    "built without the sdl feature, try --terminal".to_string()))
+        },
+        cli::Mode::Bench(options) => {
+            run_bench(&config, &options)
+        },
+        cli::Mode::ValidateTiles { .. } | cli::Mode::Engine => {
+            unreachable!("handled before loading config")
+        },
+    }
+}
+
+
+// Print the report on the standard output, and how each solver did overall
+// on the error output.
+fn run_bench(config: &Rc<config::AppConfig>, options: &bench::BenchOptions) -> error::Result<()> {
+    let records = bench::run(config, options)?;
+
+    let stdout = std::io::stdout();
+    bench::write_report(&records, options.format, &mut stdout.lock())?;
+    for line in bench::summary(&records) {
+        eprintln!("{}", line);
+    }
+
+    Ok(())
+}
+
+
+// Start bidding on a new target, when there are computer players to bid
+// against.
+#[cfg(feature = "sdl")]
+fn new_solo_round(game: &mut game::Game, opponents: &[opponent::Opponent], countdown: f32)
+    -> Option<opponent::SoloRound> {
+    if opponents.is_empty() {
+        return None;
+    }
+
+    game.clear_undo_stack();
+    game.state.reset_rand_target();
+    Some(opponent::SoloRound::start(opponents, &game.state, countdown))
+}
+
+
+#[cfg(feature = "sdl")]
+fn bidder_name(opponents: &[opponent::Opponent], bidder: opponent::Bidder) -> &str {
+    match bidder {
+        opponent::Bidder::Player => "You",
+        opponent::Bidder::Opponent(index) => &opponents[index].name,
+    }
+}
+
+
+// Lint every tile set of a config, failing when there is any warning.
+fn validate_tiles(config_path: &std::path::Path) -> error::Result<()> {
+    let config = config::load(config_path)?;
+    let mut count = 0;
+
+    for tile_set in config.tile_sets.iter() {
+        for warning in board::lint_tile_set(tile_set) {
+            println!("warning: {}", warning);
+            count += 1;
+        }
+    }
+
+    if count > 0 {
+        error::bail!(error::ErrorKind::TileWarnings(count));
+    }
+
+    println!("{} tile set(s) checked, no warning.", config.tile_sets.len());
+    Ok(())
+}
+
+
+#[cfg(feature = "sdl")]
+fn run_window(config: &Rc<config::AppConfig>, mut client: Option<net::Client>)
+    -> error::Result<()> {
+    let mut config = config.clone();
     let sdl_context = sdl2::init()?;
     let video_subsystem = sdl_context.video()?;
     let _image_context = image::init(InitFlag::PNG)?;
 
     let window = video_subsystem
-        .window("Ricochet robot",
+        .window(WINDOW_TITLE,
                 config.window.width as u32,
                 config.window.height as u32)
         .position_centered()
         .resizable()
+        .allow_highdpi()
         .build()
         .expect("could not initialize video subsystem");
 
@@ -47,17 +165,46 @@ fn main() -> error::Result<()> {
     let creator = canvas.texture_creator();
 
     let draw_ctx = graphics::DrawContext::new(&mut canvas, &creator);
-    draw_ctx.tm.borrow_mut().load_static(&config.assets_path)?;
-
     let mut renderer = graphics::Renderer::new(draw_ctx);
 
-    let mut game = game::Game::new();
+    let mut themes = config.themes();
+    let mut theme_index = 0;
+    renderer.set_theme(&config, &themes[theme_index])?;
+
+    let mut game = game::Game::with_animation(config.animation.clone());
+
+    let mut board_builder = board::Builder::new(&config);
+    let mut board_seed = 0;
+    match client.as_mut() {
+        Some(client) => {
+            // The board comes from the server, wait for it before rendering.
+            while !net::apply_message(&mut game, &client.recv()?)? {
+            }
+        },
+        None => {
+            board_seed = board_builder.build_on(&mut game.state)?;
+            game.reset_rand_pos();
+        },
+    }
 
-    let board_builder = board::Builder::new(&config);
-    board_builder.build_on(&mut game.state);
-    game.reset_rand_pos();
+    let mut opponents: Vec<_> = config.solo.opponents.iter()
+        .map(|opponent| opponent::Opponent::new(opponent, &config))
+        .collect();
+    let mut solo_round =
+        if client.is_none() { new_solo_round(&mut game, &opponents, config.solo.countdown) }
+        else { None };
 
     let mut kb_controller = game::KeyboardController::new();
+    let spectating = client.as_ref()
+        .map_or(false, |client| client.role() == net::Role::Spectator);
+
+    let mut show_heatmap = false;
+    let mut hinter = engine::Hinter::new(&config);
+    let mut pending_hint: Option<engine::PendingHint> = None;
+    let mut hovered = None;
+
+    let mut config_watcher = config::ConfigWatcher::new(config::DEFAULT_PATH);
+    let mut config_time = Instant::now();
 
     let mut event_pump = sdl_context.event_pump()?;
     let mut time = Instant::now();
@@ -68,13 +215,103 @@ fn main() -> error::Result<()> {
                 | Event::KeyDown { keycode: Some(Keycode::Escape), .. } => {
                     break 'running
                 },
-                Event::KeyDown { keycode: Some(Keycode::B), .. } => {
+                Event::Window { win_event, .. } => {
+                    renderer.handle_window_event(&win_event)?;
+                },
+                Event::KeyDown { keycode: Some(Keycode::T), repeat: false, .. } => {
+                    let next_index = (theme_index + 1) % themes.len();
+                    match renderer.set_theme(&config, &themes[next_index]) {
+                        Ok(()) => {
+                            theme_index = next_index;
+                            println!("Theme: {}", themes[theme_index].name);
+                        },
+                        Err(error) => {
+                            println!("Theme '{}' not loaded: {}",
+                                     themes[next_index].name, error.display_chain());
+                        },
+                    }
+                },
+                Event::KeyDown { keycode: Some(Keycode::H), repeat: false, .. } => {
+                    show_heatmap = !show_heatmap;
+                },
+                Event::KeyDown { keycode: Some(Keycode::I), repeat: false, .. } => {
+                    if pending_hint.is_some() {
+                        println!("Still looking for a hint.");
+                    }
+                    else {
+                        match hinter.start(&game.state) {
+                            Ok(pending) => pending_hint = Some(pending),
+                            Err(error) => eprintln!("{}", error.display_chain()),
+                        }
+                    }
+                },
+                Event::KeyDown { keycode: Some(Keycode::F3), repeat: false, .. } => {
+                    renderer.set_debug_overlay(!renderer.debug_overlay());
+                },
+                Event::MouseMotion { x, y, .. } if renderer.debug_overlay() => {
+                    let pos = renderer.cell_at(game.state.board.as_ref(), x, y);
+                    if pos != hovered {
+                        hovered = pos;
+                        renderer.set_hovered(hovered);
+                    }
+                },
+                Event::KeyDown { .. } if spectating => {},
+                Event::KeyDown { keycode: Some(Keycode::B), .. } if client.is_none() => {
                     renderer.invalidate_board();
-                    board_builder.build_on(&mut game.state);
-                    game.reset_rand_pos();
+                    game.rebuild_board(|state| board_builder.build_on(state)
+                                       .map(|seed| board_seed = seed))?;
+                    solo_round = new_solo_round(&mut game, &opponents, config.solo.countdown);
+                },
+                Event::KeyDown { keycode: Some(Keycode::G), repeat: false, .. }
+                    if client.is_none() => {
+                    let source = match board_builder.source() {
+                        board::BoardSource::Tiles => board::BoardSource::Generated,
+                        board::BoardSource::Generated => board::BoardSource::Tiles,
+                    };
+                    board_builder.set_source(source);
+                    println!("Board source: {:?}", source);
+                    renderer.invalidate_board();
+                    game.rebuild_board(|state| board_builder.build_on(state)
+                                       .map(|seed| board_seed = seed))?;
+                    solo_round = new_solo_round(&mut game, &opponents, config.solo.countdown);
                 },
-                Event::KeyDown { keycode: Some(Keycode::R), .. } => {
+                Event::KeyDown { keycode: Some(Keycode::R), .. } if client.is_none() => {
                     game.reset_rand_pos();
+                    solo_round = new_solo_round(&mut game, &opponents, config.solo.countdown);
+                },
+                Event::KeyDown { keycode: Some(Keycode::Space), repeat: false, .. } => {
+                    if let Some(client) = client.as_mut() {
+                        let moves = game.history().len();
+                        if moves > 0 {
+                            client.send(&net::Message::Bid { moves })?;
+                        }
+                    }
+                    else if let Some(round) = solo_round.as_mut() {
+                        let moves = game.history().len();
+                        if moves > 0 && round.place_bid(opponent::Bidder::Player, moves) {
+                            println!("You bid {} move(s), {:.0} second(s) left to bid.",
+                                     moves, round.time_left().unwrap_or(0.0));
+                        }
+                    }
+                },
+                Event::KeyDown { keycode: Some(Keycode::Return), repeat: false, .. } => {
+                    if let Some(client) = client.as_mut() {
+                        let moves = game.history();
+                        // The server replays the solution from where it started.
+                        game.undo_all()?;
+                        client.send(&net::Message::Solution { moves })?;
+                    }
+                    else if let Some(round) = solo_round.as_mut() {
+                        if let Some((opponent::Bidder::Player, bid)) = round.demonstrating() {
+                            let success = game.state.is_target_reached()
+                                && game.history().len() <= bid;
+                            if !success {
+                                println!("Target not reached in {} move(s).", bid);
+                                game.undo_all()?;
+                            }
+                            round.demonstrated(success);
+                        }
+                    }
                 },
                 Event::KeyDown { keycode: Some(Keycode::PageUp), .. } => {
                     if !game.undo()? {
@@ -87,11 +324,25 @@ fn main() -> error::Result<()> {
                     }
                 }
                 Event::KeyDown { keycode: Some(Keycode::Home), repeat: false, .. } => {
-                    while game.undo()? {
-                    }
+                    game.undo_all()?;
                 }
                 Event::KeyDown { keycode: Some(Keycode::End), repeat: false, .. } => {
-                    while game.redo()? {
+                    game.redo_all()?;
+                }
+                Event::KeyDown { keycode: Some(Keycode::LeftBracket), .. } => {
+                    if !game.switch_sibling(false)? {
+                        println!("No other move from here.");
+                    }
+                }
+                Event::KeyDown { keycode: Some(Keycode::RightBracket), .. } => {
+                    if !game.switch_sibling(true)? {
+                        println!("No other move from here.");
+                    }
+                }
+                Event::KeyDown { keycode: Some(Keycode::V), repeat: false, .. } => {
+                    match game.switch_variant()? {
+                        Some((index, count)) => println!("Variant {}/{}", index, count),
+                        None => println!("No other variant."),
                     }
                 }
                 _ => {
@@ -100,12 +351,119 @@ fn main() -> error::Result<()> {
             }
         }
 
+        if let Some(client) = client.as_mut() {
+            while let Some(message) = client.try_recv()? {
+                if net::apply_message(&mut game, &message)? {
+                    renderer.invalidate_board();
+                }
+                match message {
+                    net::Message::Board(_)
+                    | net::Message::Robots(_)
+                    | net::Message::Demonstration { .. } => {},
+                    other => println!("Server: {:?}", other),
+                }
+            }
+        }
+
+        if let Some(hint) = pending_hint.as_ref().and_then(|pending| pending.poll()) {
+            pending_hint = None;
+            match hint {
+                Ok(Some(hint)) => println!("Hint from {}", hint.describe()),
+                Ok(None) => println!("No solution found."),
+                Err(error) => eprintln!("{}", error.display_chain()),
+            }
+        }
+
+        if config_time.elapsed() >= CONFIG_POLL_PERIOD {
+            config_time = Instant::now();
+
+            // Keep playing with the previous config when the new one is wrong,
+            // nothing being changed until the board and theme are loaded.
+            let reloaded = config_watcher.poll().map(|result| result
+                .and_then(|new_config| {
+                    let new_config = Rc::new(new_config);
+                    let mut new_builder = board::Builder::new(&new_config);
+                    // Keep the source switched to while playing.
+                    if board_builder.source() != config.board_source {
+                        new_builder.set_source(board_builder.source());
+                    }
+                    let mut built = game::GameState::new();
+                    if client.is_none() {
+                        new_builder.build_with_seed(&mut built, board_seed)?;
+                    }
+                    let new_themes = new_config.themes();
+                    let new_index = theme_index.min(new_themes.len() - 1);
+                    renderer.set_theme(&new_config, &new_themes[new_index])?;
+                    Ok((new_config, new_builder, built, new_themes, new_index))
+                }));
+
+            match reloaded {
+                Some(Ok((new_config, new_builder, built, new_themes, new_index))) => {
+                    config = new_config;
+                    hinter.update(&config);
+                    board_builder = new_builder;
+                    themes = new_themes;
+                    theme_index = new_index;
+                    opponents = config.solo.opponents.iter()
+                        .map(|opponent| opponent::Opponent::new(opponent, &config))
+                        .collect();
+                    if client.is_none() {
+                        renderer.invalidate_board();
+                        game.replace_board(built);
+                        solo_round = new_solo_round(&mut game, &opponents, config.solo.countdown);
+                    }
+                    renderer.set_title(WINDOW_TITLE)?;
+                    println!("Config reloaded.");
+                },
+                Some(Err(error)) => {
+                    println!("Config not reloaded: {}", error.display_chain());
+                    renderer.set_title(&format!("{} - config error: {}", WINDOW_TITLE, error))?;
+                },
+                None => {},
+            }
+        }
+
         let frame_time = Instant::now();
         // TODO: use as_secs_f32 when available in stable.
         let elapsed = frame_time.duration_since(time).as_micros() as f32 * 0.000001;
 
+        if let Some(round) = solo_round.as_mut() {
+            for event in round.update(elapsed) {
+                match event {
+                    opponent::RoundEvent::Bid { bidder, moves } => {
+                        println!("{} bids {} move(s).", bidder_name(&opponents, bidder), moves);
+                    },
+                    opponent::RoundEvent::Demonstrate {
+                        bidder: opponent::Bidder::Opponent(index), moves
+                    } => {
+                        println!("{} shows {} move(s).", opponents[index].name, moves);
+                        let steps = round.steps(index).unwrap_or(&[]).to_vec();
+                        let success = opponent::play_steps(&mut game, &steps)?;
+                        round.demonstrated(success);
+                    },
+                    opponent::RoundEvent::Demonstrate { bidder: opponent::Bidder::Player, moves } => {
+                        println!("Show your {} move(s), then press Return.", moves);
+                    },
+                }
+            }
+        }
+        // The next round starts once the winning solution is shown.
+        let winner = solo_round.as_ref().and_then(|round| round.winner());
+        if let (Some(winner), false) = (winner, game.is_animating()) {
+            println!("{} won the round.", bidder_name(&opponents, winner));
+            solo_round = new_solo_round(&mut game, &opponents, config.solo.countdown);
+        }
+
         game.update_animation(elapsed);
-        renderer.render(game.state.board.as_ref(), &game.world)?;
+        // Moves are only computed again when robots or the board changed.
+        let robot = kb_controller.current_robot();
+        if !show_heatmap {
+            renderer.set_heatmap(None);
+        }
+        else if !renderer.heatmap().is_some_and(|heatmap| heatmap.is_up_to_date(&game.state, robot)) {
+            renderer.set_heatmap(game::Reachability::compute(&game.state, robot));
+        }
+        renderer.render(game.state.board.as_ref(), &game.world, game.state.target.as_ref())?;
 
         time = frame_time;
         ::std::thread::sleep(Duration::new(0, 1_000_000_000u32 / 60));
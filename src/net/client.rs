@@ -0,0 +1,135 @@
+use std::io::BufReader;
+use std::net::{TcpStream, ToSocketAddrs, Shutdown};
+use std::sync::mpsc::{channel, Receiver, TryRecvError, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+use crate::game::Game;
+
+use super::error::*;
+use super::protocol::{Message, PlayerId, Role, read_message, write_message};
+
+
+/// Connection to a server, messages are received in a background thread.
+pub struct Client {
+    stream: TcpStream,
+    incoming: Receiver<Result<Message>>,
+    player: Option<PlayerId>,
+    role: Role,
+}
+
+
+impl Client {
+    pub fn connect<A>(addr: A, name: &str, role: Role) -> Result<Client>
+        where A: ToSocketAddrs
+    {
+        let stream = TcpStream::connect(addr)?;
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let (sender, incoming) = channel();
+
+        thread::spawn(move || loop {
+            let message = read_message(&mut reader);
+            let failed = message.is_err();
+
+            if sender.send(message).is_err() || failed {
+                break;
+            }
+        });
+
+        let mut client = Client { stream, incoming, player: None, role };
+        client.send(&Message::Hello { name: name.to_string(), role })?;
+        Ok(client)
+    }
+
+
+    /// Identifier given by the server, known once `Welcome` is received.
+    #[allow(dead_code)]
+    pub fn player(&self) -> Option<PlayerId> {
+        self.player
+    }
+
+
+    pub fn role(&self) -> Role {
+        self.role
+    }
+
+
+    pub fn send(&mut self, message: &Message) -> Result<()> {
+        write_message(&mut self.stream, message)
+    }
+
+
+    pub fn recv(&mut self) -> Result<Message> {
+        match self.incoming.recv() {
+            Ok(message) => self.received(message),
+            Err(_) => bail!(ErrorKind::Disconnected),
+        }
+    }
+
+
+    pub fn try_recv(&mut self) -> Result<Option<Message>> {
+        match self.incoming.try_recv() {
+            Ok(message) => self.received(message).map(Some),
+            Err(TryRecvError::Empty) => Ok(None),
+            Err(TryRecvError::Disconnected) => bail!(ErrorKind::Disconnected),
+        }
+    }
+
+
+    #[allow(dead_code)]
+    pub fn recv_timeout(&mut self, timeout: Duration) -> Result<Option<Message>> {
+        match self.incoming.recv_timeout(timeout) {
+            Ok(message) => self.received(message).map(Some),
+            Err(RecvTimeoutError::Timeout) => Ok(None),
+            Err(RecvTimeoutError::Disconnected) => bail!(ErrorKind::Disconnected),
+        }
+    }
+
+
+    fn received(&mut self, message: Result<Message>) -> Result<Message> {
+        let message = message?;
+        if let Message::Welcome { player } = message {
+            self.player = Some(player);
+        }
+        Ok(message)
+    }
+}
+
+
+impl Drop for Client {
+    fn drop(&mut self) {
+        let _ = self.stream.shutdown(Shutdown::Both);
+    }
+}
+
+
+/// Mirror the server state stream on a local game.
+///
+/// Returns `true` when the board changed, so it can be rendered again.
+pub fn apply_message(game: &mut Game, message: &Message) -> Result<bool> {
+    match message {
+        Message::Board(snapshot) => {
+            snapshot.apply_on(game.state.board.as_mut())?;
+            return Ok(true);
+        },
+        Message::Robots(robots) => {
+            for robot in robots.iter() {
+                if let Some(state_robot) = game.state.robot_mut(robot.robot) {
+                    state_robot.pos = robot.pos;
+                }
+            }
+            game.clear_undo_stack();
+            game.world.reset(&game.state);
+        },
+        Message::Target(target) => {
+            game.state.target = target.clone();
+            game.clear_undo_stack();
+        },
+        Message::Demonstration { moves, .. } => {
+            game.demonstrate(moves.clone())?;
+        },
+        _ => {},
+    }
+
+    Ok(false)
+}
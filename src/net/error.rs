@@ -0,0 +1,45 @@
+use error_chain::error_chain;
+pub use error_chain::bail; // Re-export
+
+use crate::game;
+use crate::board;
+
+
+error_chain! {
+    types {
+        Error, ErrorKind, ResultExt, Result;
+    }
+
+    foreign_links {
+        Io(std::io::Error);
+        Json(serde_json::Error);
+    }
+
+    links {
+        GameError(game::error::Error, game::error::ErrorKind);
+        BoardError(board::error::Error, board::error::ErrorKind);
+    }
+
+    errors {
+        ProtocolVersionMismatch(expected: u32, received: u32) {
+            description("protocol version mismatch"),
+            display("protocol version mismatch (expected={}, received={})",
+                    expected, received),
+        }
+
+        Disconnected {
+            description("connection closed"),
+            display("connection closed"),
+        }
+
+        NotJoined {
+            description("no hello received"),
+            display("say hello before playing"),
+        }
+
+        UnexpectedMessage(message: String) {
+            description("unexpected message"),
+            display("unexpected message: {}", message),
+        }
+    }
+}
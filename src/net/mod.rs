@@ -0,0 +1,9 @@
+pub mod error;
+
+mod protocol;
+mod server;
+mod client;
+
+pub use self::protocol::{Message, Role};
+pub use self::server::Server;
+pub use self::client::{Client, apply_message};
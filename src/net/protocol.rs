@@ -0,0 +1,104 @@
+use std::io::{BufRead, Write};
+
+use serde_derive::{Serialize, Deserialize};
+
+use crate::positionning::LogicalPos;
+use crate::board::BoardSnapshot;
+use crate::game::{RobotId, Target, SerializedCommand, GameState};
+
+use super::error::*;
+
+
+/// Bumped each time a message changes in an incompatible way.
+pub const PROTOCOL_VERSION: u32 = 3;
+
+
+pub type PlayerId = usize;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Role {
+    Player,
+    /// Only receives the game state, cannot bid nor demonstrate.
+    Spectator,
+}
+
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RobotPos {
+    pub robot: RobotId,
+    pub pos: Option<LogicalPos>,
+}
+
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Message {
+    // Client to server
+    Hello { name: String, role: Role },
+    Bid { moves: usize },
+    Solution { moves: Vec<SerializedCommand> },
+
+    // Server to client
+    Welcome { player: PlayerId },
+    PlayerJoined { player: PlayerId, name: String },
+    PlayerLeft { player: PlayerId },
+    Board(BoardSnapshot),
+    Robots(Vec<RobotPos>),
+    Target(Option<Target>),
+    Demonstration { player: PlayerId, moves: Vec<SerializedCommand> },
+    BidPlaced { player: PlayerId, moves: usize },
+    SolutionRejected { player: PlayerId, reason: String },
+    RoundWon { player: PlayerId, moves: usize },
+    Error(String),
+}
+
+
+impl Message {
+    pub fn robots_of(state: &GameState) -> Message {
+        Message::Robots(state.robots.iter()
+            .map(|robot| RobotPos { robot: robot.id, pos: robot.pos })
+            .collect())
+    }
+}
+
+
+// Every line sent on the wire is one envelope, serialized as JSON.
+#[derive(Serialize)]
+struct OutgoingEnvelope<'m> {
+    version: u32,
+    message: &'m Message,
+}
+
+
+// Decoded in two steps, so that a message from another version of the
+// protocol is reported as such instead of as a parse error.
+#[derive(Deserialize)]
+struct IncomingEnvelope {
+    version: u32,
+    message: serde_json::Value,
+}
+
+
+pub fn write_message<W: Write>(writer: &mut W, message: &Message) -> Result<()> {
+    let envelope = OutgoingEnvelope { version: PROTOCOL_VERSION, message };
+    let mut line = serde_json::to_string(&envelope)?;
+    line.push('\n');
+    writer.write_all(line.as_bytes())?;
+    writer.flush()?;
+    Ok(())
+}
+
+
+pub fn read_message<R: BufRead>(reader: &mut R) -> Result<Message> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        bail!(ErrorKind::Disconnected);
+    }
+
+    let envelope: IncomingEnvelope = serde_json::from_str(line.trim_end())?;
+    if envelope.version != PROTOCOL_VERSION {
+        bail!(ErrorKind::ProtocolVersionMismatch(PROTOCOL_VERSION, envelope.version));
+    }
+
+    Ok(serde_json::from_value(envelope.message)?)
+}
@@ -0,0 +1,636 @@
+use std::io::{self, BufReader};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs, SocketAddr, Shutdown};
+use std::rc::Rc;
+use std::sync::mpsc::{channel, Sender, Receiver};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::config::AppConfig;
+use crate::board::{Builder, BoardSnapshot};
+use crate::game::{Game, SerializedCommand};
+
+use super::error::*;
+use super::protocol::{Message, PlayerId, Role, read_message, write_message};
+
+
+enum ClientEvent {
+    Received(PlayerId, Message),
+    Failed(PlayerId, Error),
+}
+
+
+struct Connection {
+    player: PlayerId,
+    name: Option<String>,
+    role: Role,
+    stream: TcpStream,
+}
+
+
+impl Connection {
+    fn is_player(&self) -> bool {
+        self.name.is_some() && self.role == Role::Player
+    }
+}
+
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        // Wake up the reading thread, which owns a clone of the stream.
+        let _ = self.stream.shutdown(Shutdown::Both);
+    }
+}
+
+
+/// Owns the authoritative game, and keep every client in sync with it.
+pub struct Server {
+    listener: TcpListener,
+    game: Game,
+    builder: Builder,
+    clients: Vec<Connection>,
+    events: Receiver<ClientEvent>,
+    event_sender: Sender<ClientEvent>,
+    next_player: PlayerId,
+    bids: Vec<(PlayerId, usize)>,
+    time: Instant,
+}
+
+
+impl Server {
+    pub fn bind<A>(addr: A, config: &Rc<AppConfig>) -> Result<Server>
+        where A: ToSocketAddrs
+    {
+        let listener = TcpListener::bind(addr)?;
+        listener.set_nonblocking(true)?;
+
+        let (event_sender, events) = channel();
+
+        let mut server = Server {
+            listener,
+            game: Game::new(),
+            builder: Builder::new(config),
+            clients: Vec::new(),
+            events,
+            event_sender,
+            next_player: 0,
+            bids: Vec::new(),
+            time: Instant::now(),
+        };
+
+        server.new_board()?;
+        Ok(server)
+    }
+
+
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        Ok(self.listener.local_addr()?)
+    }
+
+
+    pub fn run(&mut self) -> Result<()> {
+        println!("Server listening on {}", self.local_addr()?);
+
+        loop {
+            self.poll()?;
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+
+    /// Accept new clients and process received messages, without blocking.
+    pub fn poll(&mut self) -> Result<()> {
+        self.accept_pending()?;
+
+        while let Ok(event) = self.events.try_recv() {
+            match event {
+                ClientEvent::Received(player, message) => {
+                    self.process_message(player, message)?;
+                },
+                ClientEvent::Failed(player, error) => {
+                    match error.kind() {
+                        ErrorKind::Disconnected => {},
+                        _ => { println!("Player #{} dropped: {}", player, error); },
+                    }
+                    self.remove_client(player);
+                },
+            }
+        }
+
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.time).as_micros() as f32 * 0.000001;
+        self.game.update_animation(elapsed);
+        self.time = now;
+
+        Ok(())
+    }
+
+
+    pub fn new_board(&mut self) -> Result<()> {
+        self.builder.build_on(&mut self.game.state)?;
+        self.game.reset_rand_pos();
+        self.new_round();
+        Ok(())
+    }
+
+
+    fn new_round(&mut self) {
+        self.bids.clear();
+        self.game.clear_undo_stack();
+        self.game.state.reset_rand_target();
+    }
+
+
+    fn accept_pending(&mut self) -> Result<()> {
+        loop {
+            match self.listener.accept() {
+                Ok((stream, _)) => self.add_client(stream)?,
+                Err(ref error) if error.kind() == io::ErrorKind::WouldBlock => {
+                    return Ok(());
+                },
+                Err(error) => return Err(error.into()),
+            }
+        }
+    }
+
+
+    fn add_client(&mut self, stream: TcpStream) -> Result<()> {
+        stream.set_nonblocking(false)?;
+
+        let player = self.next_player;
+        self.next_player += 1;
+
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let sender = self.event_sender.clone();
+
+        thread::spawn(move || loop {
+            match read_message(&mut reader) {
+                Ok(message) => {
+                    if sender.send(ClientEvent::Received(player, message)).is_err() {
+                        break;
+                    }
+                },
+                Err(error) => {
+                    let _ = sender.send(ClientEvent::Failed(player, error));
+                    break;
+                },
+            }
+        });
+
+        self.clients.push(Connection { player, name: None, role: Role::Player, stream });
+        Ok(())
+    }
+
+
+    fn remove_client(&mut self, player: PlayerId) {
+        let was_joined = self.clients.iter()
+            .any(|client| client.player == player && client.is_player());
+
+        self.clients.retain(|client| client.player != player);
+        self.bids.retain(|&(bidder, _)| bidder != player);
+
+        if was_joined {
+            self.broadcast(&Message::PlayerLeft { player });
+        }
+    }
+
+
+    fn process_message(&mut self, player: PlayerId, message: Message) -> Result<()> {
+        let is_spectator = self.clients.iter()
+            .any(|client| client.player == player && client.role == Role::Spectator);
+        let has_joined = self.clients.iter()
+            .any(|client| client.player == player && client.name.is_some());
+
+        match message {
+            // Joining again would send the game twice and announce the
+            // player twice to the others.
+            Message::Hello { .. } if has_joined => Ok(()),
+            Message::Hello { name, role } => {
+                self.join(player, name, role)
+            },
+            Message::Bid { .. } | Message::Solution { .. } if !has_joined => {
+                let error = format!("{}", ErrorKind::NotJoined);
+                self.send(player, &Message::Error(error));
+                Ok(())
+            },
+            Message::Bid { .. } | Message::Solution { .. } if is_spectator => {
+                self.send(player, &Message::Error("spectators cannot play".to_string()));
+                Ok(())
+            },
+            Message::Bid { moves } => {
+                self.place_bid(player, moves);
+                Ok(())
+            },
+            Message::Solution { moves } => {
+                self.check_solution(player, moves)
+            },
+            other => {
+                let error = format!("{}", ErrorKind::UnexpectedMessage(format!("{:?}", other)));
+                self.send(player, &Message::Error(error));
+                Ok(())
+            },
+        }
+    }
+
+
+    fn join(&mut self, player: PlayerId, name: String, role: Role) -> Result<()> {
+        println!("Player #{} joined as '{}' ({:?})", player, name, role);
+
+        let mut messages = vec![
+            Message::Welcome { player },
+            Message::Board(BoardSnapshot::from_board(self.game.state.board.as_ref())?),
+            Message::robots_of(&self.game.state),
+            Message::Target(self.game.state.target.clone()),
+        ];
+
+        messages.extend(self.bids.iter()
+            .map(|&(player, moves)| Message::BidPlaced { player, moves }));
+
+        messages.extend(self.clients.iter()
+            .filter(|client| client.is_player())
+            .filter_map(|client| client.name.as_ref()
+                        .map(|name| Message::PlayerJoined {
+                            player: client.player,
+                            name: name.clone(),
+                        })));
+
+        for message in messages.iter() {
+            self.send(player, message);
+        }
+
+        if let Some(client) = self.clients.iter_mut().find(|client| client.player == player) {
+            client.name = Some(name.clone());
+            client.role = role;
+        }
+
+        if role == Role::Player {
+            self.broadcast(&Message::PlayerJoined { player, name });
+        }
+        Ok(())
+    }
+
+
+    // A lowered bid is placed again, after the bids already as low.
+    fn place_bid(&mut self, player: PlayerId, moves: usize) {
+        let previous = self.bids.iter().find(|&&(bidder, _)| bidder == player);
+        if previous.is_some_and(|&(_, bid)| bid <= moves) {
+            return;
+        }
+
+        self.bids.retain(|&(bidder, _)| bidder != player);
+        self.bids.push((player, moves));
+
+        self.broadcast(&Message::BidPlaced { player, moves });
+    }
+
+
+    fn check_solution(&mut self, player: PlayerId, moves: Vec<SerializedCommand>)
+        -> Result<()> {
+        // Lowest bid demonstrates first, the earliest one on a tie.
+        let best_bid = self.bids.iter()
+            .min_by_key(|&&(_, moves)| moves)
+            .cloned();
+
+        // Bids stay as they are when it is not the turn of the player.
+        let out_of_turn = best_bid.is_some_and(|(bidder, _)| bidder != player);
+        let reason = match best_bid {
+            Some(_) if out_of_turn => {
+                Some("another player has a lower bid".to_string())
+            },
+            Some((_, bid)) if moves.len() > bid => {
+                Some(format!("solution has {} moves, bid was {}", moves.len(), bid))
+            },
+            Some(_) => {
+                self.replay_solution(&moves)?
+            },
+            None => {
+                Some("no bid placed".to_string())
+            },
+        };
+
+        match reason {
+            Some(reason) => {
+                if !out_of_turn {
+                    self.bids.retain(|&(bidder, _)| bidder != player);
+                }
+                self.broadcast(&Message::SolutionRejected { player, reason });
+            },
+            None => {
+                let moves_count = moves.len();
+                self.broadcast(&Message::Demonstration { player, moves });
+                self.broadcast(&Message::RoundWon { player, moves: moves_count });
+
+                self.new_round();
+                self.broadcast(&Message::Target(self.game.state.target.clone()));
+            },
+        }
+
+        Ok(())
+    }
+
+
+    // Play the solution on the authoritative game, and return why it is
+    // wrong if it is. Moves of a wrong solution are reverted.
+    fn replay_solution(&mut self, moves: &[SerializedCommand]) -> Result<Option<String>> {
+        let mut played = 0;
+        let mut reason = None;
+
+        for (i, command) in moves.iter().enumerate() {
+            if !command.is_valid_on(&self.game.state) {
+                reason = Some(format!("move #{} is not valid", i + 1));
+                break;
+            }
+
+            self.game.exec_serialized(command.clone())?;
+            played += 1;
+        }
+
+        if reason.is_none() && !self.game.state.is_target_reached() {
+            reason = Some("target is not reached".to_string());
+        }
+
+        if reason.is_some() {
+            for _ in 0..played {
+                self.game.undo()?;
+            }
+        }
+
+        Ok(reason)
+    }
+
+
+    fn send(&mut self, player: PlayerId, message: &Message) {
+        let failed = match self.clients.iter_mut().find(|client| client.player == player) {
+            Some(client) => write_message(&mut client.stream, message).is_err(),
+            None => false,
+        };
+
+        if failed {
+            self.remove_client(player);
+        }
+    }
+
+
+    fn broadcast(&mut self, message: &Message) {
+        let failed: Vec<PlayerId> = self.clients.iter_mut()
+            .filter(|client| client.name.is_some())
+            .filter_map(|client| match write_message(&mut client.stream, message) {
+                Ok(_) => None,
+                Err(_) => Some(client.player),
+            })
+            .collect();
+
+        for player in failed {
+            self.remove_client(player);
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::io::BufReader;
+    use std::net::TcpStream;
+    use std::rc::Rc;
+    use std::sync::mpsc::{channel, Receiver};
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use crate::config::AppConfig;
+    use crate::game::{MoveRobotCommand, SerializedCommand, Target};
+    use crate::positionning::Way;
+
+    use super::super::client::Client;
+    use super::super::protocol::{Message, PlayerId, Role, read_message, write_message};
+    use super::Server;
+
+
+    const WAIT: Duration = Duration::from_secs(5);
+
+
+    fn server() -> Server {
+        let config: AppConfig = serde_json::from_str(
+            r#"{ "assets_path": "", "board_source": "generated" }"#)
+            .expect("config is valid");
+        Server::bind("127.0.0.1:0", &Rc::new(config)).expect("server binds")
+    }
+
+
+    // Poll the server until `client` receives a message matching `expected`.
+    fn wait_for<F>(server: &mut Server, client: &mut Client, expected: F) -> Message
+        where F: Fn(&Message) -> bool
+    {
+        let started = Instant::now();
+        while started.elapsed() < WAIT {
+            server.poll().expect("server polls");
+            while let Some(message) = client.try_recv().expect("client is connected") {
+                if expected(&message) {
+                    return message;
+                }
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+        panic!("expected message not received");
+    }
+
+
+    fn join(server: &mut Server, name: &str) -> (Client, PlayerId) {
+        let addr = server.local_addr().expect("server has an address");
+        let mut client = Client::connect(addr, name, Role::Player).expect("client connects");
+        let player = match wait_for(server, &mut client, |message| match message {
+            Message::Welcome { .. } => true,
+            _ => false,
+        }) {
+            Message::Welcome { player } => player,
+            _ => unreachable!(),
+        };
+
+        // Sent last to the one joining, like to every other player.
+        wait_for(server, &mut client, |message| match message {
+            Message::PlayerJoined { player: joined, .. } => *joined == player,
+            _ => false,
+        });
+        (client, player)
+    }
+
+
+    fn bid(server: &mut Server, client: &mut Client, moves: usize) {
+        client.send(&Message::Bid { moves }).expect("bid is sent");
+        wait_for(server, client, |message| match message {
+            Message::BidPlaced { moves: placed, .. } => *placed == moves,
+            _ => false,
+        });
+    }
+
+
+    // Make the target one move away for a robot, and return that move.
+    fn one_move_target(server: &mut Server) -> SerializedCommand {
+        let state = &mut server.game.state;
+        let (robot, source_pos, target_pos) = state.robots.iter()
+            .filter_map(|robot| robot.pos.map(|pos| (robot.id, pos)))
+            .flat_map(|(robot, pos)| [Way::Up, Way::Down, Way::Left, Way::Right].iter()
+                      .map(move |&way| (robot, pos, way)))
+            .map(|(robot, pos, way)| (robot, pos, state.cast_ray(&pos, way)))
+            .find(|(_, source_pos, target_pos)| target_pos != source_pos)
+            .expect("a robot can move");
+        state.target = Some(Target::new(robot, target_pos));
+        SerializedCommand::MoveRobot(MoveRobotCommand::new(robot, source_pos, target_pos))
+    }
+
+
+    fn rejection(message: &Message) -> Option<(PlayerId, &str)> {
+        match message {
+            Message::SolutionRejected { player, reason } => Some((*player, reason.as_str())),
+            _ => None,
+        }
+    }
+
+
+    #[test]
+    fn lowest_bidder_wins_with_a_valid_solution() {
+        let mut server = server();
+        let (mut alice, alice_id) = join(&mut server, "alice");
+        let (mut bob, _) = join(&mut server, "bob");
+        wait_for(&mut server, &mut alice, |message| match message {
+            Message::PlayerJoined { name, .. } => name == "bob",
+            _ => false,
+        });
+
+        let solution = one_move_target(&mut server);
+        bid(&mut server, &mut bob, 3);
+        bid(&mut server, &mut alice, 1);
+        alice.send(&Message::Solution { moves: vec![solution] }).expect("solution is sent");
+
+        match wait_for(&mut server, &mut bob, |message| match message {
+            Message::RoundWon { .. } => true,
+            _ => false,
+        }) {
+            Message::RoundWon { player, moves } => {
+                assert_eq!(player, alice_id);
+                assert_eq!(moves, 1);
+            },
+            _ => unreachable!(),
+        }
+    }
+
+
+    #[test]
+    fn solution_of_a_higher_bidder_is_rejected() {
+        let mut server = server();
+        let (mut alice, _) = join(&mut server, "alice");
+        let (mut bob, bob_id) = join(&mut server, "bob");
+
+        let solution = one_move_target(&mut server);
+        bid(&mut server, &mut alice, 1);
+        bid(&mut server, &mut bob, 3);
+        bob.send(&Message::Solution { moves: vec![solution] }).expect("solution is sent");
+
+        let message = wait_for(&mut server, &mut alice, |message| rejection(message).is_some());
+        assert_eq!(rejection(&message), Some((bob_id, "another player has a lower bid")));
+        // The bid of Bob is kept for its turn to come.
+        assert!(server.bids.iter().any(|&(bidder, moves)| bidder == bob_id && moves == 3));
+    }
+
+
+    #[test]
+    fn lowered_bid_goes_after_earlier_equal_bids() {
+        let mut server = server();
+        let (mut alice, alice_id) = join(&mut server, "alice");
+        let (mut bob, bob_id) = join(&mut server, "bob");
+
+        let solution = one_move_target(&mut server);
+        bid(&mut server, &mut alice, 5);
+        bid(&mut server, &mut bob, 3);
+        alice.send(&Message::Bid { moves: 3 }).expect("bid is sent");
+        wait_for(&mut server, &mut alice, |message| match message {
+            Message::BidPlaced { player, moves } => *player == alice_id && *moves == 3,
+            _ => false,
+        });
+        assert_eq!(server.bids, vec![(bob_id, 3), (alice_id, 3)]);
+
+        alice.send(&Message::Solution { moves: vec![solution] }).expect("solution is sent");
+        let message = wait_for(&mut server, &mut bob, |message| rejection(message).is_some());
+        assert_eq!(rejection(&message), Some((alice_id, "another player has a lower bid")));
+    }
+
+
+    #[test]
+    fn solution_with_an_invalid_move_is_rejected() {
+        let mut server = server();
+        let (mut alice, alice_id) = join(&mut server, "alice");
+
+        one_move_target(&mut server);
+        let robot = server.game.state.target.as_ref().expect("target is set").robot;
+        let source_pos = server.game.state.robot_pos(robot).expect("robots are placed");
+        // Staying in place is never a move.
+        let invalid = MoveRobotCommand::new(robot, source_pos, source_pos);
+        bid(&mut server, &mut alice, 1);
+        alice.send(&Message::Solution { moves: vec![SerializedCommand::MoveRobot(invalid)] })
+            .expect("solution is sent");
+
+        let message = wait_for(&mut server, &mut alice, |message| rejection(message).is_some());
+        assert_eq!(rejection(&message), Some((alice_id, "move #1 is not valid")));
+        assert_eq!(server.game.state.robot_pos(robot), Some(source_pos));
+    }
+
+
+    #[test]
+    fn bid_before_hello_is_refused() {
+        let mut server = server();
+        let mut stream = TcpStream::connect(server.local_addr().expect("server has an address"))
+            .expect("stream connects");
+
+        let mut reader = BufReader::new(stream.try_clone().expect("stream clones"));
+        let (sender, incoming): (_, Receiver<Message>) = channel();
+        thread::spawn(move || {
+            while let Ok(message) = read_message(&mut reader) {
+                if sender.send(message).is_err() {
+                    break;
+                }
+            }
+        });
+
+        write_message(&mut stream, &Message::Bid { moves: 2 }).expect("bid is sent");
+
+        let started = Instant::now();
+        let reply = loop {
+            server.poll().expect("server polls");
+            if let Ok(message) = incoming.try_recv() {
+                break message;
+            }
+            assert!(started.elapsed() < WAIT, "no reply received");
+            thread::sleep(Duration::from_millis(5));
+        };
+
+        match reply {
+            Message::Error(_) => {},
+            other => panic!("expected an error, got {:?}", other),
+        }
+        assert!(server.bids.is_empty());
+    }
+
+
+    #[test]
+    fn second_hello_is_ignored() {
+        let mut server = server();
+        let (mut alice, _) = join(&mut server, "alice");
+
+        alice.send(&Message::Hello { name: "alice".to_string(), role: Role::Player })
+            .expect("hello is sent");
+        // Messages of a connection are processed in order, the game would be
+        // sent again before the bid is placed.
+        alice.send(&Message::Bid { moves: 2 }).expect("bid is sent");
+        let message = wait_for(&mut server, &mut alice, |message| match message {
+            Message::Welcome { .. } | Message::PlayerJoined { .. } | Message::BidPlaced { .. } => true,
+            _ => false,
+        });
+        match message {
+            Message::BidPlaced { .. } => {},
+            other => panic!("expected only the bid, got {:?}", other),
+        }
+
+        let names: Vec<_> = server.clients.iter()
+            .filter_map(|client| client.name.clone())
+            .collect();
+        assert_eq!(names, vec!["alice".to_string()]);
+    }
+}
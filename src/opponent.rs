@@ -0,0 +1,393 @@
+//! Computer players bidding against a player playing alone.
+
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::thread;
+use std::time::Duration;
+
+use rand::Rng;
+use serde_derive::Deserialize;
+
+use crate::config::{AppConfig, EngineConfig, OpponentConfig};
+use crate::engine::{self, Command, Engine};
+use crate::game::{Game, GameState};
+use crate::game::error::{ErrorKind, Result};
+use crate::solver::{Solver, SolverLimits, Step};
+
+
+// Longest a computer player searches, past that it gives up on the round.
+const MAX_SEARCH_TIME: Duration = Duration::from_millis(1500);
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Medium,
+    Hard,
+}
+
+
+/// How a computer player of some difficulty plays.
+#[derive(Debug, Clone)]
+pub struct Skill {
+    /// Longest solution looked for.
+    pub max_moves: usize,
+    /// Time to think per move of the solution found, in seconds.
+    pub think_per_move: f32,
+    /// Chance to bid more moves than needed, between 0 and 1.
+    pub slip_chance: f64,
+}
+
+
+impl Difficulty {
+    pub fn skill(self) -> Skill {
+        match self {
+            Difficulty::Easy => Skill { max_moves: 4, think_per_move: 5.0, slip_chance: 0.3 },
+            Difficulty::Medium => Skill { max_moves: 7, think_per_move: 2.5, slip_chance: 0.1 },
+            Difficulty::Hard => Skill { max_moves: 10, think_per_move: 1.0, slip_chance: 0.0 },
+        }
+    }
+}
+
+
+pub struct Opponent {
+    pub name: String,
+    pub skill: Skill,
+    // Rounds to think about, on a thread of its own keeping the engine of the
+    // player running between rounds.
+    rounds: Sender<Round>,
+}
+
+
+// Position of a round, described like to an engine, and where to send the
+// plan for it.
+type Round = (Vec<Command>, Sender<Option<Plan>>);
+
+
+// What a computer player will bid on a round, and when.
+struct Plan {
+    bid: usize,
+    steps: Vec<Step>,
+    // Seconds since the round started.
+    announce_at: f32,
+    announced: bool,
+}
+
+
+// Plan of a computer player, searched for on another thread so that the game
+// keeps running meanwhile, `None` meaning the player gives up.
+enum Thought {
+    Thinking(Receiver<Option<Plan>>),
+    Done(Option<Plan>),
+}
+
+
+impl Opponent {
+    /// Player of `config`, solving rounds with its engine among those of
+    /// `app_config`, or the built-in solver.
+    pub fn new(config: &OpponentConfig, app_config: &AppConfig) -> Opponent {
+        let skill = config.difficulty.skill();
+        let engine_config = config.engine.as_ref()
+            .and_then(|name| app_config.engine(name))
+            .cloned();
+        let thread_skill = skill.clone();
+        let (rounds, received) = mpsc::channel::<Round>();
+
+        // Ends once the opponent is dropped, with its engine.
+        thread::spawn(move || {
+            let mut engine = None;
+            for (commands, sender) in received {
+                // Boards cannot be sent to a thread, the position is
+                // described to it like to an engine.
+                let mut state = GameState::new();
+                let plan = commands.iter()
+                    .try_for_each(|command| engine::apply_position(&mut state, command).map(|_| ()))
+                    .ok()
+                    .and_then(|_| plan(&thread_skill, engine_config.as_ref(), &mut engine, &state));
+                let _ = sender.send(plan);
+            }
+        });
+
+        Opponent {
+            name: config.name.clone(),
+            skill,
+            rounds,
+        }
+    }
+
+    // Start solving the round of `state` on the thread of the player.
+    fn think(&self, state: &GameState) -> Thought {
+        let commands = match engine::position_commands(state) {
+            Ok(commands) => commands,
+            Err(_) => return Thought::Done(None),
+        };
+        let (sender, result) = mpsc::channel();
+        // A thread that is gone drops `sender`, the player then gives up.
+        let _ = self.rounds.send((commands, sender));
+
+        Thought::Thinking(result)
+    }
+}
+
+
+// Solve the round of `state` as a player of `skill` would, with `engine`
+// when there is one. The built-in solver takes over when it fails.
+fn plan(
+        skill: &Skill,
+        engine_config: Option<&EngineConfig>,
+        engine: &mut Option<Engine>,
+        state: &GameState) -> Option<Plan> {
+    let searched = engine_config
+        .map(|engine_config| engine::search_with(engine, engine_config, state, skill.max_moves));
+    let steps = match searched {
+        Some(Ok(result)) => result.steps?,
+        _ => {
+            let limits = SolverLimits {
+                max_moves: skill.max_moves,
+                max_time: Some(MAX_SEARCH_TIME),
+                ..SolverLimits::default()
+            };
+            Solver::new(state.board.as_ref(), limits).solve(state)?.steps
+        },
+    };
+
+    let mut rng = rand::thread_rng();
+    let mut bid = steps.len();
+    if rng.gen_bool(skill.slip_chance) {
+        bid += rng.gen_range(1, 3);
+    }
+    let announce_at = skill.think_per_move * steps.len().max(1) as f32
+        * rng.gen_range(0.75, 1.25);
+
+    Some(Plan { bid, steps, announce_at, announced: false })
+}
+
+
+impl Thought {
+    // The plan, once the search is over.
+    fn plan(&mut self) -> Option<&mut Plan> {
+        if let Thought::Thinking(ref result) = *self {
+            match result.try_recv() {
+                Ok(plan) => { *self = Thought::Done(plan); },
+                Err(TryRecvError::Empty) => { return None; },
+                Err(TryRecvError::Disconnected) => { *self = Thought::Done(None); },
+            }
+        }
+
+        match *self {
+            Thought::Done(ref mut plan) => plan.as_mut(),
+            Thought::Thinking(_) => None,
+        }
+    }
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Bidder {
+    Player,
+    Opponent(usize),
+}
+
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RoundEvent {
+    /// A computer player announced how many moves it needs.
+    Bid { bidder: Bidder, moves: usize },
+    /// Bidding is over, the lowest bidder has to show its solution.
+    Demonstrate { bidder: Bidder, moves: usize },
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Phase {
+    Bidding,
+    Demonstrating(Bidder),
+    Over(Bidder),
+}
+
+
+/// A round of bids on the current target: the first bid starts a countdown,
+/// then the lowest bidder demonstrates, the next one taking over on failure.
+pub struct SoloRound {
+    thoughts: Vec<Thought>,
+    bids: Vec<(Bidder, usize)>,
+    time: f32,
+    countdown: f32,
+    deadline: Option<f32>,
+    phase: Phase,
+}
+
+
+impl SoloRound {
+    /// Let every opponent think about the round of `state`, bids being
+    /// closed `countdown` seconds after the first one.
+    pub fn start(opponents: &[Opponent], state: &GameState, countdown: f32) -> SoloRound {
+        SoloRound {
+            thoughts: opponents.iter().map(|opponent| opponent.think(state)).collect(),
+            bids: Vec::new(),
+            time: 0.0,
+            countdown,
+            deadline: None,
+            phase: Phase::Bidding,
+        }
+    }
+
+    /// Bid `moves`, only lower bids than the previous one of `bidder` being
+    /// taken.
+    pub fn place_bid(&mut self, bidder: Bidder, moves: usize) -> bool {
+        if self.phase != Phase::Bidding {
+            return false;
+        }
+
+        let previous = self.bids.iter().find(|bid| bid.0 == bidder);
+        if previous.is_some_and(|bid| bid.1 <= moves) {
+            return false;
+        }
+        // A lowered bid comes after earlier bids of as many moves.
+        self.bids.retain(|bid| bid.0 != bidder);
+        self.bids.push((bidder, moves));
+
+        if self.deadline.is_none() {
+            self.deadline = Some(self.time + self.countdown);
+        }
+        true
+    }
+
+    /// Seconds left to bid, once the countdown started.
+    pub fn time_left(&self) -> Option<f32> {
+        match self.phase {
+            Phase::Bidding => self.deadline.map(|deadline| (deadline - self.time).max(0.0)),
+            _ => None,
+        }
+    }
+
+    pub fn update(&mut self, elapsed: f32) -> Vec<RoundEvent> {
+        self.time += elapsed;
+        let mut events = Vec::new();
+
+        for i in 0..self.thoughts.len() {
+            let bid = match self.thoughts[i].plan() {
+                Some(plan) if !plan.announced && plan.announce_at <= self.time => {
+                    plan.announced = true;
+                    plan.bid
+                },
+                _ => continue,
+            };
+            if self.place_bid(Bidder::Opponent(i), bid) {
+                events.push(RoundEvent::Bid { bidder: Bidder::Opponent(i), moves: bid });
+            }
+        }
+
+        let closed = self.deadline.is_some_and(|deadline| self.time >= deadline);
+        if self.phase == Phase::Bidding && closed {
+            // Lowest bid first, the earliest one on a tie.
+            if let Some(&(bidder, moves)) = self.bids.iter().min_by_key(|bid| bid.1) {
+                self.phase = Phase::Demonstrating(bidder);
+                events.push(RoundEvent::Demonstrate { bidder, moves });
+            }
+        }
+
+        events
+    }
+
+    /// Bidder expected to demonstrate, and the moves it bid.
+    pub fn demonstrating(&self) -> Option<(Bidder, usize)> {
+        match self.phase {
+            Phase::Demonstrating(bidder) => {
+                self.bids.iter().find(|bid| bid.0 == bidder).cloned()
+            },
+            _ => None,
+        }
+    }
+
+    /// Solution of an opponent, to demonstrate it.
+    pub fn steps(&self, opponent: usize) -> Option<&[Step]> {
+        match self.thoughts.get(opponent) {
+            Some(Thought::Done(Some(plan))) => Some(plan.steps.as_slice()),
+            _ => None,
+        }
+    }
+
+    /// End the demonstration of the current bidder, the round being won or
+    /// going to the next bidder. Bids open again when there is none left.
+    pub fn demonstrated(&mut self, success: bool) {
+        let bidder = match self.phase {
+            Phase::Demonstrating(bidder) => bidder,
+            _ => return,
+        };
+
+        if success {
+            self.phase = Phase::Over(bidder);
+            return;
+        }
+
+        self.bids.retain(|bid| bid.0 != bidder);
+        self.phase = Phase::Bidding;
+        if self.bids.is_empty() {
+            self.deadline = None;
+        }
+    }
+
+    pub fn winner(&self) -> Option<Bidder> {
+        match self.phase {
+            Phase::Over(bidder) => Some(bidder),
+            _ => None,
+        }
+    }
+}
+
+
+/// Play `steps` from the position where the round started, robots moving
+/// as if a player played them.
+pub fn play_steps(game: &mut Game, steps: &[Step]) -> Result<bool> {
+    game.start_demonstration()?;
+
+    for step in steps.iter() {
+        let source_pos = game.state
+            .robot_pos(step.robot)
+            .ok_or(ErrorKind::RobotHasNoPosition)?;
+        let target_pos = game.state.cast_ray(&source_pos, step.way);
+        game.move_robot(step.robot, target_pos)?;
+    }
+
+    Ok(game.state.is_target_reached())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::game::GameState;
+
+    use super::{Bidder, RoundEvent, SoloRound};
+
+
+    #[test]
+    fn lowered_bids_come_after_earlier_equal_bids() {
+        let mut round = SoloRound::start(&[], &GameState::new(), 10.0);
+
+        assert!(round.place_bid(Bidder::Player, 5));
+        assert!(round.place_bid(Bidder::Opponent(0), 4));
+        assert!(!round.place_bid(Bidder::Player, 6));
+        assert!(round.place_bid(Bidder::Player, 4));
+
+        let events = round.update(10.0);
+        assert_eq!(events, vec![RoundEvent::Demonstrate { bidder: Bidder::Opponent(0), moves: 4 }]);
+        assert_eq!(round.demonstrating(), Some((Bidder::Opponent(0), 4)));
+    }
+
+
+    #[test]
+    fn next_bidder_takes_over_a_failed_demonstration() {
+        let mut round = SoloRound::start(&[], &GameState::new(), 10.0);
+        round.place_bid(Bidder::Opponent(0), 4);
+        round.place_bid(Bidder::Player, 6);
+        round.update(10.0);
+
+        round.demonstrated(false);
+        assert_eq!(round.update(0.0),
+                   vec![RoundEvent::Demonstrate { bidder: Bidder::Player, moves: 6 }]);
+        round.demonstrated(true);
+        assert_eq!(round.winner(), Some(Bidder::Player));
+    }
+}
@@ -0,0 +1,39 @@
+use serde_derive::Deserialize;
+
+use crate::game::RobotId;
+
+
+/// Colors telling robots apart, in markings and targets.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Palette {
+    /// Colors of the robots themselves.
+    #[default]
+    Standard,
+    /// Safe for red-green color blindness, from the Okabe-Ito palette.
+    Deuteranopia,
+    /// Like `Deuteranopia`, avoiding dark reds that look black.
+    Protanopia,
+}
+
+
+impl Palette {
+    pub fn color_of(&self, robot: RobotId) -> [u8; 3] {
+        match (self, robot) {
+            (Palette::Standard, RobotId::Red) => [220, 30, 30],
+            (Palette::Standard, RobotId::Green) => [30, 180, 30],
+            (Palette::Standard, RobotId::Blue) => [30, 60, 220],
+            (Palette::Standard, RobotId::Yellow) => [240, 210, 20],
+
+            (Palette::Deuteranopia, RobotId::Red) => [213, 94, 0],
+            (Palette::Deuteranopia, RobotId::Green) => [0, 158, 115],
+            (Palette::Deuteranopia, RobotId::Blue) => [0, 114, 178],
+            (Palette::Deuteranopia, RobotId::Yellow) => [240, 228, 66],
+
+            (Palette::Protanopia, RobotId::Red) => [230, 159, 0],
+            (Palette::Protanopia, RobotId::Green) => [0, 158, 115],
+            (Palette::Protanopia, RobotId::Blue) => [86, 180, 233],
+            (Palette::Protanopia, RobotId::Yellow) => [240, 228, 66],
+        }
+    }
+}
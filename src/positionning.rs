@@ -2,10 +2,10 @@ use std::fmt;
 use rand::{thread_rng, Rng};
 
 use cgmath::Vector2;
-use serde_derive::Deserialize;
+use serde_derive::{Serialize, Deserialize};
 
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SideLength(pub usize);
 
 impl fmt::Display for SideLength {
@@ -15,6 +15,30 @@ impl fmt::Display for SideLength {
 }
 
 
+/// Size of a board, in cells.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Dimensions {
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Dimensions {
+    pub fn square(side_length: &SideLength) -> Dimensions {
+        Dimensions { width: side_length.0, height: side_length.0 }
+    }
+
+    pub fn contains(&self, pos: &LogicalPos) -> bool {
+        pos.x < self.width && pos.y < self.height
+    }
+}
+
+impl fmt::Display for Dimensions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}x{}", self.width, self.height)
+    }
+}
+
+
 pub type LogicalPos = Vector2<usize>;
 pub type PhysicalPos = Vector2<f32>;
 
@@ -34,7 +58,7 @@ pub struct Hit {
 }
 
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 #[allow(dead_code)]
 pub enum FlipAxis {
     NoFlip,
@@ -137,7 +161,8 @@ impl PosExtra for LogicalPos {
 #[macro_export]
 macro_rules! impl_way {
     ($name:ident) => {
-        #[derive(Debug, PartialEq, Clone, Copy)]
+        #[derive(Debug, PartialEq, Clone, Copy,
+                 serde_derive::Serialize, serde_derive::Deserialize)]
         pub enum $name {
             Up,
             Down,
@@ -172,6 +197,20 @@ macro_rules! impl_way {
                         => $name::Right,
                 }
              }
+
+            #[allow(dead_code)]
+            pub fn flip(&self, axis: FlipAxis) -> $name {
+                let horizontal = axis == FlipAxis::FlipHorizontal || axis == FlipAxis::FlipBoth;
+                let vertical = axis == FlipAxis::FlipVertical || axis == FlipAxis::FlipBoth;
+
+                match *self {
+                    $name::Left if horizontal => $name::Right,
+                    $name::Right if horizontal => $name::Left,
+                    $name::Up if vertical => $name::Down,
+                    $name::Down if vertical => $name::Up,
+                    unchanged => unchanged,
+                }
+            }
         }
     }
 }
@@ -0,0 +1,414 @@
+//! Find the shortest solutions of a round, breadth first.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::positionning::{LogicalPos, PosExtra, Way};
+use crate::board::Board;
+use crate::game::{GameState, RobotId, Target, SerializedCommand, MoveRobotCommand};
+
+
+const WAYS: [Way; 4] = [Way::Up, Way::Down, Way::Left, Way::Right];
+
+
+/// Limits of a search, which grows quickly with the number of moves.
+#[derive(Debug, Clone)]
+pub struct SolverLimits {
+    pub max_moves: usize,
+    /// Positions of robots to explore before giving up.
+    pub max_states: usize,
+    pub max_time: Option<Duration>,
+    /// Set from elsewhere to give up right away.
+    pub stop: Option<Arc<AtomicBool>>,
+}
+
+
+impl Default for SolverLimits {
+    fn default() -> SolverLimits {
+        SolverLimits {
+            max_moves: 10,
+            max_states: 2_000_000,
+            max_time: None,
+            stop: None,
+        }
+    }
+}
+
+
+/// Move of a robot as far as it goes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Step {
+    pub robot: RobotId,
+    pub way: Way,
+}
+
+
+/// Moves leading a robot to its target, any robot moving.
+#[derive(Debug, Clone)]
+pub struct Solution {
+    pub steps: Vec<Step>,
+    /// Same moves, as commands to play them.
+    pub moves: Vec<SerializedCommand>,
+    /// Positions of robots explored to find it.
+    pub explored: usize,
+}
+
+
+/// How a search ended.
+#[derive(Debug, Clone)]
+pub enum Outcome {
+    Solved(Solution),
+    /// Every position robots can reach was explored, none of them solving
+    /// the round.
+    Unsolvable { explored: usize },
+    /// Limits were reached first, a solution may still exist.
+    GaveUp { explored: usize },
+}
+
+
+impl Outcome {
+    pub fn solution(self) -> Option<Solution> {
+        match self {
+            Outcome::Solved(solution) => Some(solution),
+            _ => None,
+        }
+    }
+
+    /// Positions of robots explored before the search ended.
+    pub fn explored(&self) -> usize {
+        match self {
+            Outcome::Solved(solution) => solution.explored,
+            Outcome::Unsolvable { explored } | Outcome::GaveUp { explored } => *explored,
+        }
+    }
+}
+
+
+pub struct Solver<'b> {
+    board: &'b dyn Board,
+    limits: SolverLimits,
+}
+
+
+// Robot positions reached during a search, and how.
+struct Node {
+    positions: Vec<LogicalPos>,
+    parent: usize,
+    robot: usize,
+    way: Way,
+}
+
+
+impl<'b> Solver<'b> {
+    pub fn new(board: &'b dyn Board, limits: SolverLimits) -> Solver<'b> {
+        Solver { board, limits }
+    }
+
+
+    /// Shortest solution of the round of `state` for its current target, if
+    /// there is one within limits.
+    pub fn solve(&self, state: &GameState) -> Option<Solution> {
+        let target = state.target.as_ref()?;
+        self.solve_for(state, target)
+    }
+
+
+    /// Shortest solution for `robot` to reach `target`, robots starting from
+    /// their position in `state`.
+    pub fn solve_for(&self, state: &GameState, target: &Target) -> Option<Solution> {
+        self.search_for(state, target).solution()
+    }
+
+
+    /// Same as `solve_for`, telling why no solution was found.
+    pub fn search_for(&self, state: &GameState, target: &Target) -> Outcome {
+        let robots: Vec<RobotId> = state.robots.iter().map(|robot| robot.id).collect();
+        let positions: Option<Vec<LogicalPos>> = state.robots.iter()
+            .map(|robot| robot.pos)
+            .collect();
+        let goal = robots.iter().position(|robot| *robot == target.robot);
+        let (positions, goal) = match (positions, goal) {
+            (Some(positions), Some(goal)) => (positions, goal),
+            _ => return Outcome::Unsolvable { explored: 0 },
+        };
+
+        let started = Instant::now();
+        let mut nodes = vec![Node { positions, parent: 0, robot: goal, way: Way::Up }];
+        let mut seen: HashSet<Vec<LogicalPos>> = HashSet::new();
+        seen.insert(nodes[0].positions.clone());
+
+        if nodes[0].positions[goal] == target.pos {
+            return Outcome::Solved(Solution { steps: Vec::new(), moves: Vec::new(), explored: 1 });
+        }
+
+        // Nodes of the current depth are the last ones added.
+        let mut depth_start = 0;
+        for _ in 0..self.limits.max_moves {
+            let depth_end = nodes.len();
+
+            for index in depth_start..depth_end {
+                let out_of_time = self.limits.max_time
+                    .is_some_and(|max_time| started.elapsed() >= max_time);
+                let stopped = self.limits.stop.as_ref()
+                    .is_some_and(|stop| stop.load(Ordering::Relaxed));
+                if out_of_time || stopped {
+                    return Outcome::GaveUp { explored: nodes.len() };
+                }
+
+                for robot in 0..robots.len() {
+                    for way in WAYS.iter() {
+                        let from = nodes[index].positions[robot];
+                        let to = self.destination(&nodes[index].positions, robot, *way);
+                        if to == from {
+                            continue;
+                        }
+
+                        let mut positions = nodes[index].positions.clone();
+                        positions[robot] = to;
+                        if seen.contains(&positions) {
+                            continue;
+                        }
+
+                        seen.insert(positions.clone());
+                        nodes.push(Node { positions, parent: index, robot, way: *way });
+
+                        if robot == goal && to == target.pos {
+                            return Outcome::Solved(
+                                Self::solution_to(&nodes, nodes.len() - 1, &robots));
+                        }
+                        if nodes.len() >= self.limits.max_states {
+                            return Outcome::GaveUp { explored: nodes.len() };
+                        }
+                    }
+                }
+            }
+
+            if nodes.len() == depth_end {
+                // Every position was explored.
+                return Outcome::Unsolvable { explored: nodes.len() };
+            }
+            depth_start = depth_end;
+        }
+
+        Outcome::GaveUp { explored: nodes.len() }
+    }
+
+
+    /// Whether playing `steps` from the positions of `state` brings the robot
+    /// of `target` on it, to check solutions found elsewhere.
+    pub fn is_solution(&self, state: &GameState, target: &Target, steps: &[Step]) -> bool {
+        let positions: Option<Vec<LogicalPos>> = state.robots.iter()
+            .map(|robot| robot.pos)
+            .collect();
+        let mut positions = match positions {
+            Some(positions) => positions,
+            None => return false,
+        };
+
+        for step in steps.iter() {
+            let index = match state.robot_index(step.robot) {
+                Some(index) => index,
+                None => return false,
+            };
+            positions[index] = self.destination(&positions, index, step.way);
+        }
+
+        state.robot_index(target.robot)
+            .is_some_and(|goal| positions[goal] == target.pos)
+    }
+
+
+    /// Where the robot at `index` stops going `way`, other robots being in
+    /// its way.
+    pub fn destination(&self, positions: &[LogicalPos], index: usize, way: Way) -> LogicalPos {
+        let start = &positions[index];
+        let board_hit = match self.board.hit_from(start, way) {
+            Ok(hit) => hit,
+            Err(_) => return *start,
+        };
+
+        positions.iter()
+            .enumerate()
+            .filter(|(i, _)| *i != index)
+            .filter_map(|(_, pos)| start.find_hit_to(pos, way))
+            .chain(std::iter::once(board_hit))
+            .min_by_key(|hit| hit.distance)
+            .map(|hit| hit.pos)
+            .unwrap_or(*start)
+    }
+
+
+    // Moves from the root to `index`.
+    fn solution_to(nodes: &[Node], mut index: usize, robots: &[RobotId]) -> Solution {
+        let explored = nodes.len();
+        let mut steps = Vec::new();
+        let mut moves = Vec::new();
+
+        while index != 0 {
+            let node = &nodes[index];
+            let parent = &nodes[node.parent];
+            let robot = robots[node.robot];
+            let command = MoveRobotCommand::new(
+                robot,
+                parent.positions[node.robot],
+                node.positions[node.robot]);
+            steps.push(Step { robot, way: node.way });
+            moves.push(SerializedCommand::MoveRobot(command));
+            index = node.parent;
+        }
+
+        steps.reverse();
+        moves.reverse();
+        Solution { steps, moves, explored }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::sync::atomic::AtomicBool;
+
+    use crate::positionning::{Dimensions, LogicalPos, Way};
+    use crate::wall::{Side, Wall};
+    use crate::game::{GameState, RobotId, Target};
+
+    use super::{Outcome, Solver, SolverLimits, Step};
+
+
+    // 4x4 board, a robot in each corner, and a wall under (2, 2):
+    //
+    //     R . . G
+    //     . . . .
+    //     . . _ .
+    //     B . . Y
+    fn state() -> GameState {
+        let mut state = GameState::new();
+        state.board.reset(&Dimensions { width: 4, height: 4 }).expect("board resets");
+        state.board.put_wall(&Wall { pos: LogicalPos { x: 2, y: 2 }, side: Side::Down })
+            .expect("wall is on the board");
+
+        state.place_robot(RobotId::Red, LogicalPos { x: 0, y: 0 });
+        state.place_robot(RobotId::Green, LogicalPos { x: 3, y: 0 });
+        state.place_robot(RobotId::Blue, LogicalPos { x: 0, y: 3 });
+        state.place_robot(RobotId::Yellow, LogicalPos { x: 3, y: 3 });
+        state
+    }
+
+
+    fn positions(state: &GameState) -> Vec<LogicalPos> {
+        state.robots.iter().map(|robot| robot.pos.expect("robots are placed")).collect()
+    }
+
+
+    #[test]
+    fn destination_stops_on_robots_and_walls() {
+        let state = state();
+        let solver = Solver::new(state.board.as_ref(), SolverLimits::default());
+        let positions = positions(&state);
+        let red = state.robot_index(RobotId::Red).expect("red robot exists");
+        let green = state.robot_index(RobotId::Green).expect("green robot exists");
+
+        assert_eq!(solver.destination(&positions, red, Way::Right), LogicalPos { x: 2, y: 0 });
+        assert_eq!(solver.destination(&positions, red, Way::Down), LogicalPos { x: 0, y: 2 });
+        assert_eq!(solver.destination(&positions, red, Way::Up), LogicalPos { x: 0, y: 0 });
+
+        let mut moved = positions.clone();
+        moved[red] = LogicalPos { x: 2, y: 0 };
+        assert_eq!(solver.destination(&moved, red, Way::Down), LogicalPos { x: 2, y: 2 });
+        assert_eq!(solver.destination(&moved, green, Way::Left), LogicalPos { x: 3, y: 0 });
+    }
+
+
+    #[test]
+    fn solve_for_finds_the_shortest_solution() {
+        let state = state();
+        let solver = Solver::new(state.board.as_ref(), SolverLimits::default());
+        let target = Target::new(RobotId::Red, LogicalPos { x: 2, y: 2 });
+
+        let solution = solver.solve_for(&state, &target).expect("target is reachable");
+        assert_eq!(solution.steps, vec![
+            Step { robot: RobotId::Red, way: Way::Right },
+            Step { robot: RobotId::Red, way: Way::Down },
+        ]);
+        assert_eq!(solution.moves.len(), 2);
+        assert!(solution.moves[0].is_valid_on(&state));
+    }
+
+
+    #[test]
+    fn solve_for_a_reached_target_needs_no_move() {
+        let state = state();
+        let solver = Solver::new(state.board.as_ref(), SolverLimits::default());
+        let target = Target::new(RobotId::Blue, LogicalPos { x: 0, y: 3 });
+
+        let solution = solver.solve_for(&state, &target).expect("target is reached");
+        assert!(solution.steps.is_empty());
+    }
+
+
+    #[test]
+    fn solve_for_gives_up_past_max_moves() {
+        let state = state();
+        let limits = SolverLimits { max_moves: 1, ..SolverLimits::default() };
+        let solver = Solver::new(state.board.as_ref(), limits);
+        let target = Target::new(RobotId::Red, LogicalPos { x: 2, y: 2 });
+
+        assert!(solver.solve_for(&state, &target).is_none());
+        match solver.search_for(&state, &target) {
+            Outcome::GaveUp { .. } => {},
+            other => panic!("expected the search to give up, got {:?}", other),
+        }
+    }
+
+
+    #[test]
+    fn search_for_gives_up_once_stopped() {
+        let state = state();
+        let stop = Arc::new(AtomicBool::new(true));
+        let limits = SolverLimits { stop: Some(stop), ..SolverLimits::default() };
+        let solver = Solver::new(state.board.as_ref(), limits);
+        let target = Target::new(RobotId::Red, LogicalPos { x: 2, y: 2 });
+
+        match solver.search_for(&state, &target) {
+            Outcome::GaveUp { .. } => {},
+            other => panic!("expected the search to give up, got {:?}", other),
+        }
+    }
+
+
+    #[test]
+    fn search_for_tells_unsolvable_rounds() {
+        let mut state = state();
+        // Walled in from every side.
+        let pos = LogicalPos { x: 1, y: 1 };
+        for &side in [Side::Up, Side::Down, Side::Left, Side::Right].iter() {
+            state.board.put_wall(&Wall { pos, side }).expect("wall is on the board");
+        }
+        // Enough moves to explore every position of the board.
+        let limits = SolverLimits { max_moves: 100, ..SolverLimits::default() };
+        let solver = Solver::new(state.board.as_ref(), limits);
+        let target = Target::new(RobotId::Red, pos);
+
+        match solver.search_for(&state, &target) {
+            Outcome::Unsolvable { explored } => assert!(explored > 1),
+            other => panic!("expected no solution, got {:?}", other),
+        }
+    }
+
+
+    #[test]
+    fn is_solution_replays_steps() {
+        let state = state();
+        let solver = Solver::new(state.board.as_ref(), SolverLimits::default());
+        let target = Target::new(RobotId::Red, LogicalPos { x: 2, y: 2 });
+        let right = Step { robot: RobotId::Red, way: Way::Right };
+        let down = Step { robot: RobotId::Red, way: Way::Down };
+
+        assert!(solver.is_solution(&state, &target, &[right, down]));
+        assert!(!solver.is_solution(&state, &target, &[down, right]));
+        assert!(!solver.is_solution(&state, &target, &[right]));
+    }
+}
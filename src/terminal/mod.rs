@@ -0,0 +1,163 @@
+//! Text-mode frontend, to play without SDL, like over SSH.
+
+mod view;
+
+use std::io::{self, Write};
+use std::rc::Rc;
+
+use termion::clear;
+use termion::cursor;
+use termion::event::Key;
+use termion::input::TermRead;
+use termion::raw::IntoRawMode;
+use termion::color;
+use termion::style;
+
+use crate::positionning::Way;
+use crate::config::AppConfig;
+use crate::board::{self, BoardSource};
+use crate::game::{Game, RobotId};
+use crate::engine;
+use crate::error::Result;
+
+use self::view::{board_lines, robot_color, robot_letter};
+
+
+const HELP: &str = "\
+arrows: move   1-4: select robot   PgUp/PgDn: undo/redo   Home/End: undo/redo all
+[/]: other move from here   v: next variant
+b: new board   r: new positions   g: tiles/generated   h: hint   q: quit";
+
+
+/// Play locally in the terminal until the player quits.
+pub fn run(config: &Rc<AppConfig>) -> Result<()> {
+    let stdin = io::stdin();
+    let mut stdout = io::stdout().into_raw_mode()?;
+    write!(stdout, "{}", cursor::Hide)?;
+
+    let result = play(config, stdin.keys(), &mut stdout);
+
+    write!(stdout, "{}{}\r\n", style::Reset, cursor::Show)?;
+    stdout.flush()?;
+    result
+}
+
+
+fn play<K, W>(config: &Rc<AppConfig>, keys: K, out: &mut W) -> Result<()>
+    where K: Iterator<Item = io::Result<Key>>,
+          W: Write
+{
+    // Robots jump to their place, there is no animation to wait for.
+    let mut game = Game::without_animation();
+    let mut builder = board::Builder::new(config);
+    let hinter = engine::Hinter::new(config);
+    builder.build_on(&mut game.state)?;
+    game.reset_rand_pos();
+    game.state.reset_rand_target();
+
+    let mut selected = RobotId::Red;
+    let mut status = String::new();
+    draw(out, &game, selected, &status)?;
+
+    for key in keys {
+        status.clear();
+
+        let key = key?;
+        match key {
+            Key::Char('q') | Key::Esc | Key::Ctrl('c') => break,
+            Key::Up => { game.try_move_robot_in_dir(selected, Way::Up)?; },
+            Key::Down => { game.try_move_robot_in_dir(selected, Way::Down)?; },
+            Key::Left => { game.try_move_robot_in_dir(selected, Way::Left)?; },
+            Key::Right => { game.try_move_robot_in_dir(selected, Way::Right)?; },
+            Key::Char('1') => { selected = RobotId::Red; },
+            Key::Char('2') => { selected = RobotId::Green; },
+            Key::Char('3') => { selected = RobotId::Blue; },
+            Key::Char('4') => { selected = RobotId::Yellow; },
+            Key::PageUp => {
+                let undone = game.undo()?;
+                if !undone {
+                    status = "No more action to undo.".to_string();
+                }
+            },
+            Key::PageDown => {
+                let redone = game.redo()?;
+                if !redone {
+                    status = "No more action to redo.".to_string();
+                }
+            },
+            Key::Home => { game.undo_all()?; },
+            Key::End => { game.redo_all()?; },
+            Key::Char('[') | Key::Char(']') => {
+                let switched = game.switch_sibling(key == Key::Char(']'))?;
+                if !switched {
+                    status = "No other move from here.".to_string();
+                }
+            },
+            Key::Char('v') => {
+                status = match game.switch_variant()? {
+                    Some((index, count)) => format!("Variant {}/{}", index, count),
+                    None => "No other variant.".to_string(),
+                };
+            },
+            Key::Char('b') => {
+                game.rebuild_board(|state| builder.build_on(state).map(|_| ()))?;
+                game.state.reset_rand_target();
+            },
+            Key::Char('r') => {
+                game.reset_rand_pos();
+                game.state.reset_rand_target();
+            },
+            Key::Char('g') => {
+                let source = match builder.source() {
+                    BoardSource::Tiles => BoardSource::Generated,
+                    BoardSource::Generated => BoardSource::Tiles,
+                };
+                builder.set_source(source);
+                game.rebuild_board(|state| builder.build_on(state).map(|_| ()))?;
+                game.state.reset_rand_target();
+                status = format!("Board source: {:?}", source);
+            },
+            Key::Char('h') => {
+                status = match hinter.hint(&game.state) {
+                    Ok(Some(hint)) => format!("Hint from {}", hint.describe()),
+                    Ok(None) => "No solution found.".to_string(),
+                    Err(error) => error.to_string(),
+                };
+            },
+            _ => {},
+        }
+
+        if status.is_empty() && game.state.is_target_reached() {
+            status = format!("Target reached in {} move(s)!", game.history().len());
+        }
+        draw(out, &game, selected, &status)?;
+    }
+
+    Ok(())
+}
+
+
+// Redraw the whole screen.
+fn draw<W>(out: &mut W, game: &Game, selected: RobotId, status: &str) -> Result<()>
+    where W: Write
+{
+    write!(out, "{}{}", clear::All, cursor::Goto(1, 1))?;
+
+    for line in board_lines(&game.state, selected) {
+        write!(out, "{}\r\n", line)?;
+    }
+
+    write!(out, "Robot: {}{}{}   Moves: {}",
+           color::Fg(robot_color(selected)), robot_letter(selected), style::Reset,
+           game.history().len())?;
+    if let Some(ref target) = game.state.target {
+        write!(out, "   Target: {}{}{}",
+               color::Fg(robot_color(target.robot)),
+               robot_letter(target.robot).to_ascii_lowercase(),
+               style::Reset)?;
+    }
+    write!(out, "\r\n{}\r\n{}\r\n", status, HELP.replace('\n', "\r\n"))?;
+
+    out.flush()?;
+    Ok(())
+}
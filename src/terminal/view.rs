@@ -0,0 +1,153 @@
+use termion::color::{self, AnsiValue};
+use termion::style;
+
+use crate::positionning::{LogicalPos, Dimensions};
+use crate::moves::MovePossibility;
+use crate::game::{GameState, RobotId};
+
+
+// Box-drawing characters for a junction of walls, indexed by the walls going
+// up, down, left and right of it, in this order of bits.
+const JUNCTIONS: [char; 16] = [
+    ' ', '╶', '╴', '─',
+    '╷', '┌', '┐', '┬',
+    '╵', '└', '┘', '┴',
+    '│', '├', '┤', '┼',
+];
+
+
+/// Lines drawing the board of `state`, walls in box-drawing characters and
+/// robots as colored letters, the `selected` one being highlighted.
+pub fn board_lines(state: &GameState, selected: RobotId) -> Vec<String> {
+    let view = BoardView::new(state);
+    let dimensions = view.dimensions;
+    let mut lines = Vec::new();
+
+    for y in 0..=dimensions.height {
+        let mut walls = String::new();
+        walls.push(view.junction(0, y));
+        for x in 0..dimensions.width {
+            walls.push_str(if view.horizontal_wall(x, y) { "───" } else { "   " });
+            walls.push(view.junction(x + 1, y));
+        }
+        lines.push(walls);
+
+        if y == dimensions.height {
+            break;
+        }
+
+        let mut cells = String::new();
+        for x in 0..=dimensions.width {
+            cells.push(if view.vertical_wall(x, y) { '│' } else { ' ' });
+            if x < dimensions.width {
+                cells.push_str(&cell_text(state, &LogicalPos { x, y }, &view, selected));
+            }
+        }
+        lines.push(cells);
+    }
+
+    lines
+}
+
+
+pub fn robot_color(robot: RobotId) -> AnsiValue {
+    match robot {
+        RobotId::Red => AnsiValue(9),
+        RobotId::Green => AnsiValue(10),
+        RobotId::Blue => AnsiValue(12),
+        RobotId::Yellow => AnsiValue(11),
+    }
+}
+
+
+pub fn robot_letter(robot: RobotId) -> char {
+    match robot {
+        RobotId::Red => 'R',
+        RobotId::Green => 'G',
+        RobotId::Blue => 'B',
+        RobotId::Yellow => 'Y',
+    }
+}
+
+
+// Three columns showing what stands on a cell.
+fn cell_text(state: &GameState, pos: &LogicalPos, view: &BoardView, selected: RobotId) -> String {
+    let robot = state.robots.iter()
+        .find(|robot| robot.pos.as_ref() == Some(pos))
+        .map(|robot| robot.id);
+    let target = state.target.as_ref()
+        .filter(|target| target.pos == *pos)
+        .map(|target| target.robot);
+
+    match (robot, target) {
+        (Some(robot), _) => {
+            let (open, close) =
+                if robot == selected { ('[', ']') }
+                else if target.is_some() { ('(', ')') }
+                else { (' ', ' ') };
+            format!("{}{}{}{}{}{}",
+                    open, color::Fg(robot_color(robot)), style::Bold,
+                    robot_letter(robot), style::Reset, close)
+        },
+        (None, Some(robot)) => {
+            format!(" {}{}{} ",
+                    color::Fg(robot_color(robot)),
+                    robot_letter(robot).to_ascii_lowercase(),
+                    style::Reset)
+        },
+        (None, None) if view.moves(pos).forbidden => "▒▒▒".to_string(),
+        (None, None) => "   ".to_string(),
+    }
+}
+
+
+// Walls of a board, from what robots can do on each cell.
+struct BoardView {
+    dimensions: Dimensions,
+    moves: Vec<MovePossibility>,
+}
+
+
+impl BoardView {
+    fn new(state: &GameState) -> BoardView {
+        let dimensions = state.board.dimensions();
+        let moves = (0..dimensions.width * dimensions.height)
+            .map(|i| LogicalPos { x: i % dimensions.width, y: i / dimensions.width })
+            .map(|pos| state.board.moves_from(&pos).unwrap_or_else(|_| MovePossibility::all()))
+            .collect();
+
+        BoardView { dimensions, moves }
+    }
+
+    fn moves(&self, pos: &LogicalPos) -> &MovePossibility {
+        &self.moves[pos.y * self.dimensions.width + pos.x]
+    }
+
+    // Wall above the cell at (x, y), the bottom edge of the board being
+    // above the row past the last one.
+    fn horizontal_wall(&self, x: usize, y: usize) -> bool {
+        if y == 0 || y == self.dimensions.height {
+            return true;
+        }
+        !self.moves(&LogicalPos { x, y: y - 1 }).down || !self.moves(&LogicalPos { x, y }).up
+    }
+
+    // Wall on the left of the cell at (x, y).
+    fn vertical_wall(&self, x: usize, y: usize) -> bool {
+        if x == 0 || x == self.dimensions.width {
+            return true;
+        }
+        !self.moves(&LogicalPos { x: x - 1, y }).right || !self.moves(&LogicalPos { x, y }).left
+    }
+
+    // Corner shared by cells around (x, y), on the top-left of this one.
+    fn junction(&self, x: usize, y: usize) -> char {
+        let up = y > 0 && self.vertical_wall(x, y - 1);
+        let down = y < self.dimensions.height && self.vertical_wall(x, y);
+        let left = x > 0 && self.horizontal_wall(x - 1, y);
+        let right = x < self.dimensions.width && self.horizontal_wall(x, y);
+
+        let index = (up as usize) << 3 | (down as usize) << 2 | (left as usize) << 1 | right as usize;
+        JUNCTIONS[index]
+    }
+}
@@ -1,4 +1,6 @@
-use crate::positionning::{LogicalPos, RotateAngle};
+use serde_derive::{Serialize, Deserialize};
+
+use crate::positionning::{LogicalPos, RotateAngle, FlipAxis};
 
 
 // Enum variants on type aliases are still experimental.
@@ -6,7 +8,7 @@ use crate::positionning::{LogicalPos, RotateAngle};
 impl_way!(Side);
 
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Wall {
     pub pos: LogicalPos,
     pub side: Side